@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::expression::Expr;
+use crate::pattern::{MatchArm, Pattern};
+use crate::statement::{Stmt, VarTarget};
+use crate::token::Token;
+use crate::token_literal::TokenLiteral;
+
+// Prints statements as an indented tree of S-expressions, one node per line
+// Expressions that go through the resolver show their id and the scope depth it resolved them to,
+// so `(variable a #4 depth 1)` is looked up one scope out, and `global` ones in the global environment
+pub struct AstPrinter<'a> {
+    locals: &'a HashMap<usize, usize>,
+    out: String,
+    indent: usize,
+}
+
+impl<'a> AstPrinter<'a> {
+    pub fn new(locals: &'a HashMap<usize, usize>) -> Self {
+        Self { locals, out: String::new(), indent: 0 }
+    }
+
+    pub fn print(mut self, statements: &[Stmt]) -> String {
+        for statement in statements {
+            self.stmt(statement);
+        }
+        self.out
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Block { statements } => self.node("block", |p| {
+                for statement in statements {
+                    p.stmt(statement);
+                }
+            }),
+            Stmt::Break { .. } => self.leaf("break"),
+            Stmt::Class { name, superclass, methods } => self.node(&format!("class {}", name.lexeme), |p| {
+                if let Some(superclass) = superclass {
+                    p.node("superclass", |p| p.expr(superclass));
+                }
+                for method in methods {
+                    p.stmt(method);
+                }
+            }),
+            Stmt::Continue { .. } => self.leaf("continue"),
+            Stmt::Enum { name, variants } => self.node(&format!("enum {}", name.lexeme), |p| {
+                for variant in variants {
+                    p.leaf(&format!("variant {}{}", variant.name.lexeme, AstPrinter::names(&variant.fields)));
+                }
+            }),
+            Stmt::Expression { expression } => self.node("expression", |p| p.expr(expression)),
+            Stmt::ForIn { name, iterable, body, .. } => self.node(&format!("for-in {}", name.lexeme), |p| {
+                p.expr(iterable);
+                p.stmt(body);
+            }),
+            Stmt::Function { ptr } => {
+                let kind = if ptr.is_generator { "generator" } else { "fun" };
+                self.node(&format!("{kind} {}{}", ptr.name.lexeme, AstPrinter::names(&ptr.params)), |p| {
+                    for statement in &ptr.body {
+                        p.stmt(statement);
+                    }
+                })
+            }
+            Stmt::If { expression, then_branch, else_branch } => self.node("if", |p| {
+                p.expr(expression);
+                p.node("then", |p| p.stmt(then_branch));
+                p.node("else", |p| p.stmt(else_branch));
+            }),
+            Stmt::Match { subject, arms, .. } => self.node("match", |p| {
+                p.expr(subject);
+                for arm in arms {
+                    p.arm(arm);
+                }
+            }),
+            Stmt::Print { expression } => self.node("print", |p| p.expr(expression)),
+            Stmt::Return { value, .. } => self.node("return", |p| p.expr(value)),
+            Stmt::Try { body, name, handler } => self.node("try", |p| {
+                p.stmt(body);
+                p.node(&format!("catch {}", name.lexeme), |p| p.stmt(handler));
+            }),
+            Stmt::Var { target, initializer, is_const } => {
+                let keyword = if *is_const { "const" } else { "var" };
+                let target = match target {
+                    VarTarget::Name(name) => name.lexeme.clone(),
+                    VarTarget::List { names, .. } => format!("[{}]", AstPrinter::joined(names)),
+                    VarTarget::Fields { names, .. } => format!("{{{}}}", AstPrinter::joined(names)),
+                };
+                self.node(&format!("{keyword} {target}"), |p| p.expr(initializer))
+            }
+            Stmt::Yield { value, .. } => self.node("yield", |p| p.expr(value)),
+            Stmt::While { expression, body, increment } => self.node("while", |p| {
+                p.expr(expression);
+                p.stmt(body);
+                if let Some(increment) = increment {
+                    p.node("increment", |p| p.expr(increment));
+                }
+            }),
+        }
+    }
+
+    fn arm(&mut self, arm: &MatchArm) {
+        let pattern = match &arm.pattern {
+            Pattern::Literal { value } => AstPrinter::literal(value),
+            Pattern::Wildcard => String::from("_"),
+            Pattern::Binding { name } => name.lexeme.clone(),
+            Pattern::Class { binding, .. } => format!("class {}", binding.lexeme),
+            Pattern::Enum { variant, bindings, .. } => format!("variant {}{}", variant.lexeme, AstPrinter::names(bindings)),
+        };
+        self.node(&format!("arm {pattern}"), |p| {
+            match &arm.pattern {
+                Pattern::Class { class, .. } => p.expr(class),
+                Pattern::Enum { enum_type, .. } => p.expr(enum_type),
+                _ => (),
+            }
+            if let Some(guard) = &arm.guard {
+                p.node("guard", |p| p.expr(guard));
+            }
+            p.stmt(&arm.body);
+        })
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign { name, value, id } => {
+                let label = format!("assign {}{}", name.lexeme, self.resolution(*id, true));
+                self.node(&label, |p| p.expr(value))
+            }
+            Expr::Binary { left, operator, right } | Expr::Logical { left, operator, right } => self.node(&operator.lexeme, |p| {
+                p.expr(left);
+                p.expr(right);
+            }),
+            Expr::Call { callee, arguments, .. } => self.node("call", |p| {
+                p.expr(callee);
+                for argument in arguments {
+                    p.expr(argument);
+                }
+            }),
+            Expr::Get { object, name, id } => self.node(&format!("get {} #{id}", name.lexeme), |p| p.expr(object)),
+            Expr::Grouping { expression } => self.node("group", |p| p.expr(expression)),
+            Expr::Index { object, index, .. } => self.node("index", |p| {
+                p.expr(object);
+                p.expr(index);
+            }),
+            Expr::List { elements, .. } => self.node("list", |p| {
+                for element in elements {
+                    p.expr(element);
+                }
+            }),
+            Expr::Literal { value } => self.leaf(&AstPrinter::literal(value)),
+            Expr::Map { entries, .. } => self.node("map", |p| {
+                for (key, value) in entries {
+                    p.node("entry", |p| {
+                        p.expr(key);
+                        p.expr(value);
+                    });
+                }
+            }),
+            Expr::Set { object, name, value, id } => self.node(&format!("set {} #{id}", name.lexeme), |p| {
+                p.expr(object);
+                p.expr(value);
+            }),
+            Expr::SetIndex { object, index, value, .. } => self.node("set-index", |p| {
+                p.expr(object);
+                p.expr(index);
+                p.expr(value);
+            }),
+            Expr::Super { method, id, .. } => self.leaf(&format!("super {}{}", method.lexeme, self.resolution(*id, false))),
+            Expr::This { id, .. } => self.leaf(&format!("this{}", self.resolution(*id, false))),
+            Expr::Unary { operator, right } => self.node(&operator.lexeme, |p| p.expr(right)),
+            Expr::Unpack { targets, value, .. } => self.node("unpack", |p| {
+                p.node("targets", |p| {
+                    for target in targets {
+                        p.expr(target);
+                    }
+                });
+                p.expr(value);
+            }),
+            Expr::Variable { name, id } => self.leaf(&format!("variable {}{}", name.lexeme, self.resolution(*id, true))),
+        }
+    }
+
+    // Variables the resolver leaves alone are globals; other unresolved nodes have nothing to show
+    fn resolution(&self, id: usize, may_be_global: bool) -> String {
+        match self.locals.get(&id) {
+            Some(depth) => format!(" #{id} depth {depth}"),
+            None if may_be_global => format!(" #{id} global"),
+            None => format!(" #{id}"),
+        }
+    }
+
+    fn literal(value: &TokenLiteral) -> String {
+        match value {
+            TokenLiteral::LOX_STRING(string) => format!("\"{string}\""),
+            other => other.to_string(),
+        }
+    }
+
+    fn names(names: &[Token]) -> String {
+        format!("({})", AstPrinter::joined(names))
+    }
+
+    fn joined(names: &[Token]) -> String {
+        names.iter().map(|name| name.lexeme.as_str()).collect::<Vec<_>>().join(", ")
+    }
+
+    fn leaf(&mut self, label: &str) {
+        writeln!(self.out, "{}({label})", "  ".repeat(self.indent)).unwrap();
+    }
+
+    // The closing paren goes on the line of the last child, Lisp style
+    fn node(&mut self, label: &str, children: impl FnOnce(&mut Self)) {
+        writeln!(self.out, "{}({label}", "  ".repeat(self.indent)).unwrap();
+        self.indent += 1;
+        children(self);
+        self.indent -= 1;
+        // Replaces the newline after the last child, or after the label when there were none
+        self.out.pop();
+        self.out.push_str(")\n");
+    }
+}
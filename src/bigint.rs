@@ -0,0 +1,317 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+use crate::token_literal::TokenLiteral;
+
+// Integers that no longer fit in an i64, stored as a sign and a magnitude
+// The magnitude is kept in base 2^32 digits, least significant first, without leading zero digits,
+// so zero is an empty list of digits and is never negative
+#[derive(Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+const RADIX: u64 = 1 << 32;
+// Largest power of ten that fits in a digit, used to convert to and from decimal in chunks
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+
+impl BigInt {
+    fn new(negative: bool, mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        let negative = negative && !digits.is_empty();
+        Self { negative, digits }
+    }
+
+    pub fn from_i64(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::new(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+
+    /// Widens an integer of either size, `None` for anything that isn't an integer
+    pub fn from_literal(value: &TokenLiteral) -> Option<Self> {
+        match value {
+            TokenLiteral::LOX_INT(value) => Some(BigInt::from_i64(*value)),
+            TokenLiteral::LOX_BIGINT(value) => Some(value.as_ref().clone()),
+            _ => None,
+        }
+    }
+
+    /// Truncates towards zero, `None` for NaN and infinities
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        let magnitude = value.trunc().abs();
+        if magnitude < RADIX as f64 * RADIX as f64 {
+            let magnitude = magnitude as u64;
+            return Some(BigInt::new(value < 0.0, vec![magnitude as u32, (magnitude >> 32) as u32]));
+        }
+
+        // Anything this large is a whole number: a 53-bit mantissa shifted left by the exponent
+        let bits = magnitude.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as usize - 1075;
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let digits = BigInt::shift_left(&[mantissa as u32, (mantissa >> 32) as u32], exponent);
+        Some(BigInt::new(value < 0.0, digits))
+    }
+
+    /// Parses an optionally negative string of decimal digits
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        if text.is_empty() || !text.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut digits = Vec::new();
+        for c in text.bytes() {
+            BigInt::mul_add_small(&mut digits, 10, (c - b'0') as u32);
+        }
+        Some(BigInt::new(negative, digits))
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+        let magnitude = self.digits.iter().rev().fold(0u64, |acc, &digit| (acc << 32) | digit as u64);
+        match self.negative {
+            false => i64::try_from(magnitude).ok(),
+            true if magnitude <= i64::MIN.unsigned_abs() => Some(0i64.wrapping_sub(magnitude as i64)),
+            true => None,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self.digits.iter().rev().fold(0.0, |acc, &digit| acc * RADIX as f64 + digit as f64);
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Number of bits in the magnitude, zero for zero
+    pub fn bits(&self) -> u64 {
+        match self.digits.last() {
+            Some(top) => self.digits.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    /// Results that fit back into an i64 become plain integers again
+    pub fn into_literal(self) -> TokenLiteral {
+        match self.to_i64() {
+            Some(value) => TokenLiteral::LOX_INT(value),
+            None => TokenLiteral::LOX_BIGINT(Rc::new(self)),
+        }
+    }
+
+    pub fn neg(&self) -> Self {
+        BigInt::new(!self.negative, self.digits.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> Self {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, BigInt::add_magnitudes(&self.digits, &other.digits));
+        }
+        // Opposite signs: the result takes the sign of the operand with the larger magnitude
+        match BigInt::cmp_magnitudes(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::new(other.negative, BigInt::sub_magnitudes(&other.digits, &self.digits)),
+            _ => BigInt::new(self.negative, BigInt::sub_magnitudes(&self.digits, &other.digits)),
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> Self {
+        let mut digits = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, &left) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &right) in other.digits.iter().enumerate() {
+                let product = left as u64 * right as u64 + digits[i + j] as u64 + carry;
+                digits[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            digits[i + other.digits.len()] = carry as u32;
+        }
+        BigInt::new(self.negative != other.negative, digits)
+    }
+
+    /// Quotient truncated towards zero and a remainder with the sign of the dividend, like i64 division
+    /// Returns `None` when dividing by zero
+    pub fn divmod(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = BigInt::divmod_magnitudes(&self.digits, &other.digits);
+        Some((BigInt::new(self.negative != other.negative, quotient), BigInt::new(self.negative, remainder)))
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> Self {
+        let mut result = BigInt::from_i64(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul(&base);
+            }
+        }
+        result
+    }
+
+    fn cmp_magnitudes(left: &[u32], right: &[u32]) -> Ordering {
+        left.len().cmp(&right.len()).then_with(|| left.iter().rev().cmp(right.iter().rev()))
+    }
+
+    fn add_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+        let (longer, shorter) = if left.len() >= right.len() { (left, right) } else { (right, left) };
+        let mut digits = Vec::with_capacity(longer.len() + 1);
+        let mut carry = 0u64;
+        for (i, &digit) in longer.iter().enumerate() {
+            let sum = digit as u64 + *shorter.get(i).unwrap_or(&0) as u64 + carry;
+            digits.push(sum as u32);
+            carry = sum >> 32;
+        }
+        digits.push(carry as u32);
+        digits
+    }
+
+    /// Requires `left >= right`
+    fn sub_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+        let mut digits = Vec::with_capacity(left.len());
+        let mut borrow = 0i64;
+        for (i, &digit) in left.iter().enumerate() {
+            let mut difference = digit as i64 - *right.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = (difference < 0) as i64;
+            if difference < 0 {
+                difference += RADIX as i64;
+            }
+            digits.push(difference as u32);
+        }
+        digits
+    }
+
+    /// Shift-and-subtract long division, one bit of the dividend at a time
+    fn divmod_magnitudes(dividend: &[u32], divisor: &[u32]) -> (Vec<u32>, Vec<u32>) {
+        if divisor.len() == 1 {
+            let mut quotient = dividend.to_vec();
+            let remainder = BigInt::div_small(&mut quotient, divisor[0]);
+            return (quotient, vec![remainder]);
+        }
+
+        let mut quotient = vec![0u32; dividend.len()];
+        let mut remainder: Vec<u32> = Vec::new();
+        for bit in (0..dividend.len() * 32).rev() {
+            remainder = BigInt::shift_left(&remainder, 1);
+            if dividend[bit / 32] >> (bit % 32) & 1 == 1 {
+                match remainder.first_mut() {
+                    Some(lowest) => *lowest |= 1,
+                    None => remainder.push(1),
+                }
+            }
+            if BigInt::cmp_magnitudes(&remainder, divisor) != Ordering::Less {
+                remainder = BigInt::sub_magnitudes(&remainder, divisor);
+                quotient[bit / 32] |= 1 << (bit % 32);
+            }
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+        }
+        (quotient, remainder)
+    }
+
+    fn shift_left(digits: &[u32], bits: usize) -> Vec<u32> {
+        let (whole, partial) = (bits / 32, bits % 32);
+        let mut shifted = vec![0u32; whole];
+        let mut carry = 0u32;
+        for &digit in digits {
+            shifted.push(if partial == 0 { digit } else { (digit << partial) | carry });
+            carry = if partial == 0 { 0 } else { digit >> (32 - partial) };
+        }
+        shifted.push(carry);
+        while shifted.last() == Some(&0) {
+            shifted.pop();
+        }
+        shifted
+    }
+
+    /// Divides in place, returning the remainder
+    fn div_small(digits: &mut [u32], divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for digit in digits.iter_mut().rev() {
+            let current = (remainder << 32) | *digit as u64;
+            *digit = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        remainder as u32
+    }
+
+    fn mul_add_small(digits: &mut Vec<u32>, multiplier: u32, addend: u32) {
+        let mut carry = addend as u64;
+        for digit in digits.iter_mut() {
+            let product = *digit as u64 * multiplier as u64 + carry;
+            *digit = product as u32;
+            carry = product >> 32;
+        }
+        if carry > 0 {
+            digits.push(carry as u32);
+        }
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => BigInt::cmp_magnitudes(&self.digits, &other.digits),
+            (true, true) => BigInt::cmp_magnitudes(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // Peel off nine decimal digits at a time, least significant first
+        let mut magnitude = self.digits.clone();
+        let mut chunks = Vec::new();
+        while !magnitude.is_empty() {
+            chunks.push(BigInt::div_small(&mut magnitude, DECIMAL_CHUNK));
+            while magnitude.last() == Some(&0) {
+                magnitude.pop();
+            }
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        write!(f, "{}", chunks.next().unwrap())?;
+        for chunk in chunks {
+            write!(f, "{chunk:09}")?;
+        }
+        Ok(())
+    }
+}
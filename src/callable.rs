@@ -1,6 +1,7 @@
 use std::fmt::{Display, Formatter};
 use std::rc::Rc;
 use crate::class::LoxClass;
+use crate::enum_type::EnumConstructor;
 use crate::function::LoxFunction;
 use crate::interpreter::{Interpreter, InterpreterError};
 use crate::token_literal::TokenLiteral;
@@ -10,6 +11,7 @@ pub enum LoxCallable {
     Native(NativeFunction),
     UserFunction(Rc<LoxFunction>),
     ClassConstructor(Rc<LoxClass>),
+    EnumConstructor(EnumConstructor),
 }
 
 impl LoxCallable {
@@ -18,13 +20,40 @@ impl LoxCallable {
             LoxCallable::Native(native) => native.call(interpreter, arguments),
             LoxCallable::UserFunction(function) => function.call(interpreter, arguments),
             LoxCallable::ClassConstructor(class) => class.call(interpreter, arguments),
+            LoxCallable::EnumConstructor(constructor) => constructor.call(interpreter, arguments),
         }
     }
-    pub fn arity(&self) -> usize {
+    pub fn arity(&self) -> Arity {
         match self {
             LoxCallable::Native(native) => native.arity(),
-            LoxCallable::UserFunction(function) => function.arity(),
+            LoxCallable::UserFunction(function) => Arity::Exact(function.arity()),
             LoxCallable::ClassConstructor(class) => class.arity(),
+            LoxCallable::EnumConstructor(constructor) => Arity::Exact(constructor.arity()),
+        }
+    }
+}
+
+// How many arguments a callable takes; only natives can be variadic
+#[derive(Clone, Copy)]
+pub enum Arity {
+    Exact(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    pub fn accepts(&self, count: usize) -> bool {
+        match self {
+            Arity::Exact(arity) => count == *arity,
+            Arity::AtLeast(minimum) => count >= *minimum,
+        }
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Arity::Exact(arity) => write!(f, "{arity}"),
+            Arity::AtLeast(minimum) => write!(f, "at least {minimum}"),
         }
     }
 }
@@ -32,9 +61,12 @@ impl LoxCallable {
 impl Display for LoxCallable {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            LoxCallable::Native(NativeFunction::Host(host)) => write!(f, "<native fn {}>", host.name),
+            LoxCallable::Native(NativeFunction::BoundMethod(bound)) => write!(f, "<native fn {}>", bound.method.name),
             LoxCallable::Native(_) => write!(f, "<native fn>"),
             LoxCallable::UserFunction(function) => write!(f, "{function}"),
-            LoxCallable::ClassConstructor(lox_class) => write!(f, "{lox_class}")
+            LoxCallable::ClassConstructor(lox_class) => write!(f, "{lox_class}"),
+            LoxCallable::EnumConstructor(constructor) => write!(f, "{constructor}")
         }
     }
 }
@@ -2,28 +2,45 @@ use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
 use std::rc::Rc;
-use crate::callable::LoxCallable;
+use crate::callable::{Arity, LoxCallable};
 
 use crate::class_instance::LoxInstance;
 use crate::function::LoxFunction;
 use crate::interpreter::{Interpreter, InterpreterError};
+use crate::native_class::{NativeConstructor, NativeMethod};
 use crate::token_literal::TokenLiteral;
 
 pub struct LoxClass {
     name: String,
     superclass: Option<Rc<LoxClass>>,
     methods: HashMap<String, Rc<LoxFunction>>,
+    // Only set for classes defined by the host
+    constructor: Option<NativeConstructor>,
+    native_methods: HashMap<String, Rc<NativeMethod>>,
 }
 
 impl LoxClass {
     pub fn new(name: String, superclass: Option<Rc<LoxClass>>, methods: HashMap<String, Rc<LoxFunction>>) -> Self {
-        Self { name, superclass, methods }
+        Self { name, superclass, methods, constructor: None, native_methods: HashMap::new() }
     }
 
-    pub fn call(&self, interpreter: &mut Interpreter, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+    /// A class implemented by the host, whose instances wrap the handle built by `constructor`
+    pub fn new_native(name: String, constructor: NativeConstructor, native_methods: Vec<NativeMethod>) -> Self {
+        let native_methods = native_methods.into_iter().map(|method| (method.name.clone(), Rc::new(method))).collect();
+        Self { name, superclass: None, methods: HashMap::new(), constructor: Some(constructor), native_methods }
+    }
+
+    pub fn call(&self, interpreter: &mut Interpreter, mut arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
         if let Some(TokenLiteral::LOX_CALLABLE(constructor)) = arguments.last() {
             if let LoxCallable::ClassConstructor(class) = constructor.deref() {
-                let instance = LoxInstance::new(Rc::clone(class));
+                let class = Rc::clone(class);
+                if let Some(constructor) = self.native_constructor() {
+                    arguments.pop();
+                    let handle = constructor.call(interpreter, arguments)?;
+                    return Ok(TokenLiteral::LOX_INSTANCE(Rc::new(LoxInstance::with_handle(class, handle))));
+                }
+
+                let instance = LoxInstance::new(class);
                 let instance = Rc::new(instance);
 
                 let initializer = self.find_method(&String::from("init"));
@@ -39,10 +56,23 @@ impl LoxClass {
         unreachable!("Last argument to class constructor call must be pointer to class object")
     }
 
-    pub fn arity(&self) -> usize {
+    pub fn arity(&self) -> Arity {
+        if let Some(constructor) = self.native_constructor() {
+            return constructor.arity;
+        }
         match self.find_method(&String::from("init")) {
-            Some(initializer) => initializer.arity(),
-            None => 0
+            Some(initializer) => Arity::Exact(initializer.arity()),
+            None => Arity::Exact(0)
+        }
+    }
+
+    pub fn is_subclass_of(&self, other: &LoxClass) -> bool {
+        if std::ptr::eq(self, other) {
+            return true;
+        }
+        match &self.superclass {
+            Some(superclass) => superclass.is_subclass_of(other),
+            None => false
         }
     }
 
@@ -51,11 +81,30 @@ impl LoxClass {
             let method = self.methods.get(key).unwrap();
             return Some(Rc::clone(method));
         }
-        if self.superclass.is_some() {
-            return self.superclass.as_ref().unwrap().find_method(key);
+        if let Some(superclass) = &self.superclass {
+            return superclass.find_method(key);
         }
         None
     }
+
+    /// Subclasses of a native class without an initializer of their own still get their handle built
+    fn native_constructor(&self) -> Option<&NativeConstructor> {
+        if self.methods.contains_key("init") {
+            return None;
+        }
+        match &self.constructor {
+            Some(constructor) => Some(constructor),
+            None => self.superclass.as_ref().and_then(|superclass| superclass.native_constructor()),
+        }
+    }
+
+    /// Native methods are inherited like user-defined ones, but user-defined methods take precedence
+    pub fn find_native_method(&self, key: &str) -> Option<Rc<NativeMethod>> {
+        if let Some(method) = self.native_methods.get(key) {
+            return Some(Rc::clone(method));
+        }
+        self.superclass.as_ref().and_then(|superclass| superclass.find_native_method(key))
+    }
 }
 
 impl Display for LoxClass{
@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
@@ -6,17 +7,30 @@ use crate::callable::LoxCallable;
 
 use crate::class::LoxClass;
 use crate::interpreter::InterpreterError;
+use crate::native_class::BoundNativeMethod;
+use crate::native_function::NativeFunction;
 use crate::token::Token;
 use crate::token_literal::TokenLiteral;
 
 pub struct LoxInstance {
     pub class: Rc<LoxClass>,
     fields: RefCell<HashMap<String, TokenLiteral>>,
+    // Host data wrapped by instances of native classes, opaque to scripts
+    handle: Option<Rc<dyn Any>>,
 }
 
 impl LoxInstance {
     pub fn new(class: Rc<LoxClass>) -> Self {
-        Self { class, fields: RefCell::new(HashMap::new()) }
+        Self { class, fields: RefCell::new(HashMap::new()), handle: None }
+    }
+
+    pub fn with_handle(class: Rc<LoxClass>, handle: Rc<dyn Any>) -> Self {
+        Self { class, fields: RefCell::new(HashMap::new()), handle: Some(handle) }
+    }
+
+    /// The wrapped host data, if this instance has some of type `T`
+    pub fn handle<T: 'static>(&self) -> Option<Rc<T>> {
+        self.handle.clone()?.downcast::<T>().ok()
     }
 
     pub fn get(&self, self_rc: Rc<Self>, name: &Token) -> Result<TokenLiteral, InterpreterError> {
@@ -30,10 +44,22 @@ impl LoxInstance {
             return Ok(function);
         }
 
+        if let Some(method) = self.class.find_native_method(&name.lexeme) {
+            let method = NativeFunction::BoundMethod(BoundNativeMethod::new(self_rc, method));
+            return Ok(TokenLiteral::LOX_CALLABLE(Rc::new(LoxCallable::Native(method))));
+        }
+
         let err_msg = format!("Undefined property '{}'", name.lexeme);
         Err(InterpreterError::OperatorError {err_msg, line: name.line})
     }
 
+    /// Fields sorted by name, for output that doesn't depend on hashing order
+    pub fn fields(&self) -> Vec<(String, TokenLiteral)> {
+        let mut fields: Vec<(String, TokenLiteral)> = self.fields.borrow().iter().map(|(name, value)| (name.clone(), value.clone())).collect();
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        fields
+    }
+
     pub fn set(&self, name: &Token, value: TokenLiteral) {
         self.fields.borrow_mut().insert(name.lexeme.clone(), value);
     }
@@ -1,16 +1,307 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::interpreter::InterpreterError;
-use crate::token_literal::TokenLiteral::{self, LOX_NUMBER};
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::map::LoxMap;
+use crate::token_literal::TokenLiteral::{self, LOX_INT, LOX_MAP, LOX_NULL, LOX_NUMBER, LOX_STRING};
 
 pub struct Clock;
 
 impl Clock {
     pub fn time_since_epoch_as_secs() -> Result<TokenLiteral, InterpreterError> {
-        Ok(LOX_NUMBER(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()))
+        Ok(LOX_NUMBER(Clock::now_secs()))
     }
 
-    pub fn arity() -> usize {
-        0
+    fn now_secs() -> f64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
     }
-}
\ No newline at end of file
+}
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+// A UTC calendar date and time, down to the millisecond
+struct DateTime {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    millisecond: i64,
+}
+
+impl DateTime {
+    fn from_timestamp(timestamp: f64) -> Self {
+        let millis = (timestamp * 1000.0).floor() as i64;
+        let (days, millis) = (millis.div_euclid(86_400_000), millis.rem_euclid(86_400_000));
+        let (year, month, day) = DateTime::civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            hour: millis / 3_600_000,
+            minute: millis / 60_000 % 60,
+            second: millis / 1000 % 60,
+            millisecond: millis % 1000,
+        }
+    }
+
+    fn timestamp(&self) -> f64 {
+        let days = DateTime::days_from_civil(self.year, self.month, self.day);
+        let seconds = days * 86_400 + self.hour * 3600 + self.minute * 60 + self.second;
+        seconds as f64 + self.millisecond as f64 / 1000.0
+    }
+
+    /// ISO numbering, Monday is 1 and Sunday is 7
+    fn weekday(&self) -> i64 {
+        // 1970-01-01 was a Thursday
+        (DateTime::days_from_civil(self.year, self.month, self.day) + 3).rem_euclid(7) + 1
+    }
+
+    // Howard Hinnant's algorithms, exact over the whole proleptic Gregorian calendar
+    fn civil_from_days(days: i64) -> (i64, i64, i64) {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        (year, month, day)
+    }
+
+    fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let mp = if month > 2 { month - 3 } else { month + 9 };
+        let day_of_year = (153 * mp + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    fn days_in_month(year: i64, month: i64) -> i64 {
+        match month {
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    fn to_map(&self) -> TokenLiteral {
+        let mut map = LoxMap::new();
+        let fields = [
+            ("year", self.year),
+            ("month", self.month),
+            ("day", self.day),
+            ("hour", self.hour),
+            ("minute", self.minute),
+            ("second", self.second),
+            ("millisecond", self.millisecond),
+            ("weekday", self.weekday()),
+        ];
+        for (name, value) in fields {
+            map.insert(LOX_STRING(Rc::new(String::from(name))), LOX_INT(value));
+        }
+        map.insert(LOX_STRING(Rc::new(String::from("timestamp"))), LOX_NUMBER(self.timestamp()));
+        LOX_MAP(Rc::new(RefCell::new(map)))
+    }
+}
+
+// Calendar and timing natives
+// Timestamps are seconds since the Unix epoch like `clock()`, and dates are always in UTC
+// Patterns use %Y %m %d %H %M %S, %f for milliseconds, %b and %a for month and weekday names, and %% for a literal %
+#[derive(Clone, Copy)]
+pub enum TimeNative {
+    Now,
+    Utc,
+    FormatTime,
+    ParseTime,
+    Elapsed,
+    Sleep,
+}
+
+impl TimeNative {
+    pub const ALL: [TimeNative; 6] = [
+        TimeNative::Now,
+        TimeNative::Utc,
+        TimeNative::FormatTime,
+        TimeNative::ParseTime,
+        TimeNative::Elapsed,
+        TimeNative::Sleep,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimeNative::Now => "now",
+            TimeNative::Utc => "utc",
+            TimeNative::FormatTime => "formatTime",
+            TimeNative::ParseTime => "parseTime",
+            TimeNative::Elapsed => "elapsed",
+            TimeNative::Sleep => "sleep",
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            TimeNative::Now | TimeNative::Elapsed => 0,
+            TimeNative::Utc | TimeNative::Sleep => 1,
+            TimeNative::FormatTime | TimeNative::ParseTime => 2,
+        }
+    }
+
+    pub fn call(&self, interpreter: &mut Interpreter, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match self {
+            // A map of the calendar fields, along with the timestamp they were taken from
+            TimeNative::Now => Ok(DateTime::from_timestamp(Clock::now_secs()).to_map()),
+            TimeNative::Utc => Ok(DateTime::from_timestamp(self.timestamp(&arguments[0])?).to_map()),
+            TimeNative::FormatTime => {
+                let date = DateTime::from_timestamp(self.timestamp(&arguments[0])?);
+                self.format(&date, self.pattern(&arguments[1])?).map(|text| LOX_STRING(Rc::new(text)))
+            }
+            TimeNative::ParseTime => match &arguments[0] {
+                LOX_STRING(text) => Ok(LOX_NUMBER(self.parse(text, self.pattern(&arguments[1])?)?.timestamp())),
+                other => Err(self.error(format!("expects a string to parse but got {other}."))),
+            },
+            // Monotonic seconds since the interpreter started, unaffected by changes to the system clock
+            TimeNative::Elapsed => Ok(LOX_NUMBER(interpreter.started.elapsed().as_secs_f64())),
+            TimeNative::Sleep => {
+                let millis = match arguments[0] {
+                    LOX_INT(millis) if millis >= 0 => millis as f64,
+                    LOX_NUMBER(millis) if millis >= 0.0 && millis.is_finite() => millis,
+                    ref other => return Err(self.error(format!("expects a non-negative number of milliseconds but got {other}."))),
+                };
+                let duration = Duration::try_from_secs_f64(millis / 1000.0)
+                    .map_err(|_| self.error(format!("can't sleep for {} milliseconds.", arguments[0])))?;
+                thread::sleep(duration);
+                Ok(LOX_NULL)
+            }
+        }
+    }
+
+    fn error(&self, message: String) -> InterpreterError {
+        InterpreterError::NativeError(format!("{}() {message}", self.name()))
+    }
+
+    // A number of seconds, or a date map as returned by `now` and `utc`
+    fn timestamp(&self, value: &TokenLiteral) -> Result<f64, InterpreterError> {
+        match value {
+            LOX_INT(seconds) => Ok(*seconds as f64),
+            LOX_NUMBER(seconds) if seconds.is_finite() => Ok(*seconds),
+            LOX_MAP(map) => match map.borrow().get(&LOX_STRING(Rc::new(String::from("timestamp")))) {
+                Some(timestamp @ (LOX_INT(_) | LOX_NUMBER(_))) => self.timestamp(&timestamp),
+                _ => Err(self.error(String::from("expects a date map with a numeric 'timestamp' entry."))),
+            },
+            other => Err(self.error(format!("expects a timestamp but got {other}."))),
+        }
+    }
+
+    fn pattern<'a>(&self, value: &'a TokenLiteral) -> Result<&'a str, InterpreterError> {
+        match value {
+            LOX_STRING(pattern) => Ok(pattern.as_str()),
+            other => Err(self.error(format!("expects a pattern string but got {other}."))),
+        }
+    }
+
+    fn format(&self, date: &DateTime, pattern: &str) -> Result<String, InterpreterError> {
+        let mut out = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => write!(out, "{:04}", date.year).unwrap(),
+                Some('m') => write!(out, "{:02}", date.month).unwrap(),
+                Some('d') => write!(out, "{:02}", date.day).unwrap(),
+                Some('H') => write!(out, "{:02}", date.hour).unwrap(),
+                Some('M') => write!(out, "{:02}", date.minute).unwrap(),
+                Some('S') => write!(out, "{:02}", date.second).unwrap(),
+                Some('f') => write!(out, "{:03}", date.millisecond).unwrap(),
+                Some('b') => out.push_str(MONTHS[date.month as usize - 1]),
+                Some('a') => out.push_str(WEEKDAYS[date.weekday() as usize - 1]),
+                Some('%') => out.push('%'),
+                Some(other) => return Err(self.error(format!("does not know the directive '%{other}'."))),
+                None => return Err(self.error(String::from("pattern ends with a lone '%'."))),
+            }
+        }
+        Ok(out)
+    }
+
+    // The inverse of `format`; fields missing from the pattern default to the start of the epoch
+    fn parse(&self, text: &str, pattern: &str) -> Result<DateTime, InterpreterError> {
+        let mismatch = || self.error(format!("could not match '{text}' against '{pattern}'."));
+        let mut date = DateTime { year: 1970, month: 1, day: 1, hour: 0, minute: 0, second: 0, millisecond: 0 };
+        let mut rest = text;
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                rest = rest.strip_prefix(c).ok_or_else(mismatch)?;
+                continue;
+            }
+            let directive = chars.next();
+            match directive {
+                Some('Y') => {
+                    let negative = rest.starts_with('-');
+                    let (year, remaining) = TimeNative::digits(&rest[negative as usize..], 4).ok_or_else(mismatch)?;
+                    date.year = if negative { -year } else { year };
+                    rest = remaining;
+                }
+                Some(field @ ('m' | 'd' | 'H' | 'M' | 'S' | 'f')) => {
+                    let width = if field == 'f' { 3 } else { 2 };
+                    let (value, remaining) = TimeNative::digits(rest, width).ok_or_else(mismatch)?;
+                    match field {
+                        'm' => date.month = value,
+                        'd' => date.day = value,
+                        'H' => date.hour = value,
+                        'M' => date.minute = value,
+                        'S' => date.second = value,
+                        _ => date.millisecond = value,
+                    }
+                    rest = remaining;
+                }
+                Some(names @ ('b' | 'a')) => {
+                    let table: &[&str] = if names == 'b' { &MONTHS } else { &WEEKDAYS };
+                    let index = table.iter().position(|name| rest.starts_with(name)).ok_or_else(mismatch)?;
+                    if names == 'b' {
+                        date.month = index as i64 + 1;
+                    }
+                    rest = &rest[table[index].len()..];
+                }
+                Some('%') => rest = rest.strip_prefix('%').ok_or_else(mismatch)?,
+                Some(other) => return Err(self.error(format!("does not know the directive '%{other}'."))),
+                None => return Err(self.error(String::from("pattern ends with a lone '%'."))),
+            }
+        }
+        if !rest.is_empty() {
+            return Err(mismatch());
+        }
+
+        let valid = (1..=12).contains(&date.month)
+            && (1..=DateTime::days_in_month(date.year, date.month)).contains(&date.day)
+            && date.hour < 24
+            && date.minute < 60
+            && date.second < 60;
+        if !valid {
+            return Err(self.error(format!("'{text}' is not a valid date.")));
+        }
+        Ok(date)
+    }
+
+    // Exactly `width` digits
+    fn digits(text: &str, width: usize) -> Option<(i64, &str)> {
+        let field = text.get(..width)?;
+        if !field.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        Some((field.parse().ok()?, &text[width..]))
+    }
+}
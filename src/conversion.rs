@@ -0,0 +1,114 @@
+use std::rc::Rc;
+
+use crate::bigint::BigInt;
+use crate::callable::LoxCallable;
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::token_literal::TokenLiteral::{self, *};
+
+// int(value) -- truncates floats towards zero and parses strings
+pub struct Int;
+
+// float(value) -- widens integers and parses strings
+pub struct Float;
+
+// str(value) -- the same text `print` would show
+pub struct Str;
+
+// num(value) -- parses strings as an integer when possible, a float otherwise
+pub struct Num;
+
+// bool(value) -- whether the value is truthy
+pub struct Bool;
+
+// typeof(value) -- the name of the value's type
+pub struct TypeOf;
+
+impl Int {
+    pub fn call(arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match &arguments[0] {
+            LOX_INT(value) => Ok(LOX_INT(*value)),
+            LOX_BIGINT(value) => Ok(LOX_BIGINT(Rc::clone(value))),
+            LOX_NUMBER(value) => match BigInt::from_f64(*value) {
+                Some(value) => Ok(value.into_literal()),
+                None => Err(InterpreterError::NativeError(format!("Can't convert {value} to an integer."))),
+            },
+            LOX_STRING(value) => match BigInt::parse(value.trim()) {
+                Some(value) => Ok(value.into_literal()),
+                None => Err(InterpreterError::NativeError(format!("Can't convert \"{value}\" to an integer."))),
+            },
+            other => Err(InterpreterError::NativeError(format!("Can't convert {other} to an integer."))),
+        }
+    }
+}
+
+impl Float {
+    pub fn call(arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match &arguments[0] {
+            LOX_INT(value) => Ok(LOX_NUMBER(*value as f64)),
+            LOX_BIGINT(value) => Ok(LOX_NUMBER(value.to_f64())),
+            LOX_NUMBER(value) => Ok(LOX_NUMBER(*value)),
+            LOX_STRING(value) => match value.trim().parse::<f64>() {
+                Ok(value) => Ok(LOX_NUMBER(value)),
+                Err(_) => Err(InterpreterError::NativeError(format!("Can't convert \"{value}\" to a float."))),
+            },
+            other => Err(InterpreterError::NativeError(format!("Can't convert {other} to a float."))),
+        }
+    }
+}
+
+impl Str {
+    pub fn call(arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match &arguments[0] {
+            LOX_STRING(value) => Ok(LOX_STRING(Rc::clone(value))),
+            other => Ok(LOX_STRING(Rc::new(other.to_string()))),
+        }
+    }
+}
+
+impl Num {
+    pub fn call(arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match &arguments[0] {
+            value @ (LOX_INT(_) | LOX_BIGINT(_) | LOX_NUMBER(_)) => Ok(value.clone()),
+            LOX_STRING(value) => {
+                let text = value.trim();
+                if let Some(integer) = BigInt::parse(text) {
+                    return Ok(integer.into_literal());
+                }
+                // Rust also parses "inf" and "NaN", which aren't Lox number literals
+                match text.parse::<f64>() {
+                    Ok(number) if number.is_finite() => Ok(LOX_NUMBER(number)),
+                    _ => Err(InterpreterError::NativeError(format!("num() can't parse \"{value}\" as a number."))),
+                }
+            }
+            other => Err(InterpreterError::NativeError(format!("num() expects a string or number but got {other}."))),
+        }
+    }
+}
+
+impl Bool {
+    pub fn call(arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        Ok(LOX_BOOL(Interpreter::is_truthy(&arguments[0])))
+    }
+}
+
+impl TypeOf {
+    pub fn call(arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        let name = match &arguments[0] {
+            LOX_NUMBER(_) | LOX_INT(_) | LOX_BIGINT(_) => "number",
+            LOX_STRING(_) => "string",
+            LOX_BOOL(_) => "bool",
+            LOX_NULL => "nil",
+            LOX_CALLABLE(callable) => match callable.as_ref() {
+                LoxCallable::ClassConstructor(_) => "class",
+                _ => "function",
+            },
+            LOX_INSTANCE(_) => "instance",
+            LOX_LIST(_) => "list",
+            LOX_MAP(_) => "map",
+            LOX_GENERATOR(_) => "generator",
+            LOX_ENUM(_) => "enum",
+            LOX_ENUM_VALUE(_) => "variant",
+        };
+        Ok(LOX_STRING(Rc::new(String::from(name))))
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt::{Display, Formatter};
+use std::iter::zip;
+use std::rc::Rc;
+
+use crate::callable::LoxCallable;
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::token::Token;
+use crate::token_literal::TokenLiteral;
+
+pub struct LoxEnum {
+    pub name: String,
+    variants: Vec<EnumVariant>,
+}
+
+pub struct EnumVariant {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+// A value of one of an enum's variants, along with its associated values
+pub struct EnumValue {
+    pub enum_type: Rc<LoxEnum>,
+    pub variant: usize,
+    pub values: Vec<TokenLiteral>,
+}
+
+impl LoxEnum {
+    pub fn new(name: String, variants: Vec<EnumVariant>) -> Self {
+        Self { name, variants }
+    }
+
+    pub fn variant_index(&self, name: &str) -> Option<usize> {
+        self.variants.iter().position(|variant| variant.name == name)
+    }
+
+    pub fn variant(&self, index: usize) -> &EnumVariant {
+        &self.variants[index]
+    }
+
+    /// Unit variants are values themselves, variants with fields are constructors
+    pub fn get(self_rc: &Rc<Self>, name: &Token) -> Result<TokenLiteral, InterpreterError> {
+        match self_rc.variant_index(&name.lexeme) {
+            Some(variant) if self_rc.variants[variant].fields.is_empty() => {
+                let value = EnumValue { enum_type: Rc::clone(self_rc), variant, values: Vec::new() };
+                Ok(TokenLiteral::LOX_ENUM_VALUE(Rc::new(value)))
+            }
+            Some(variant) => {
+                let constructor = EnumConstructor { enum_type: Rc::clone(self_rc), variant };
+                Ok(TokenLiteral::LOX_CALLABLE(Rc::new(LoxCallable::EnumConstructor(constructor))))
+            }
+            None => {
+                let err_msg = format!("Enum '{}' has no variant '{}'", self_rc.name, name.lexeme);
+                Err(InterpreterError::OperatorError { line: name.line, err_msg })
+            }
+        }
+    }
+}
+
+impl Display for LoxEnum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<enum {}>", self.name)
+    }
+}
+
+pub struct EnumConstructor {
+    enum_type: Rc<LoxEnum>,
+    variant: usize,
+}
+
+impl EnumConstructor {
+    pub fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        let value = EnumValue { enum_type: Rc::clone(&self.enum_type), variant: self.variant, values: arguments };
+        Ok(TokenLiteral::LOX_ENUM_VALUE(Rc::new(value)))
+    }
+
+    pub fn arity(&self) -> usize {
+        self.enum_type.variants[self.variant].fields.len()
+    }
+}
+
+impl Display for EnumConstructor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<variant {}.{}>", self.enum_type.name, self.enum_type.variants[self.variant].name)
+    }
+}
+
+impl EnumValue {
+    pub fn get(&self, name: &Token) -> Result<TokenLiteral, InterpreterError> {
+        let variant = self.enum_type.variant(self.variant);
+        match variant.fields.iter().position(|field| field == &name.lexeme) {
+            Some(i) => Ok(self.values[i].clone()),
+            None => {
+                let err_msg = format!("Undefined property '{}'", name.lexeme);
+                Err(InterpreterError::OperatorError { line: name.line, err_msg })
+            }
+        }
+    }
+
+    /// Values are equal when they are the same variant of the same enum and hold equal values
+    pub fn equals(&self, other: &EnumValue) -> bool {
+        Rc::ptr_eq(&self.enum_type, &other.enum_type)
+            && self.variant == other.variant
+            && zip(self.values.iter(), other.values.iter()).all(|(left, right)| Interpreter::is_equal(left.clone(), right.clone()))
+    }
+}
+
+impl Display for EnumValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.enum_type.name, self.enum_type.variant(self.variant).name)?;
+        if !self.values.is_empty() {
+            write!(f, "(")?;
+            for (i, value) in self.values.iter().enumerate() {
+                if i > 0 { write!(f, ", ")?; }
+                value.fmt_nested(f)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -1,30 +1,49 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 use std::rc::Rc;
 
-use crate::callable::LoxCallable;
-use crate::clock::Clock;
-use crate::interpreter::InterpreterError;
-use crate::native_function::NativeFunction;
+use crate::callable::{Arity, LoxCallable};
+use crate::class::LoxClass;
+use crate::clock::{Clock, TimeNative};
+use crate::conversion::{Bool, Float, Int, Num, Str, TypeOf};
+use crate::file_io::FileNative;
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::math::MathNative;
+use crate::native_function::{HostFunction, NativeFunction};
+use crate::json::JsonNative;
+use crate::process::ProcessNative;
+use crate::random::RandomNative;
+use crate::testing::AssertNative;
+use crate::strings::{string_builder_class, StringNative};
 use crate::token_literal::TokenLiteral;
 use crate::token::Token;
 
 #[derive(Default)]
 pub struct Environment {
     values: RefCell<HashMap<String, TokenLiteral>>,
+    constants: RefCell<HashSet<String>>,
     pub enclosing: Option<Rc<Environment>>,
 }
 
 impl Environment {
     pub fn new (enclosing: Option<Rc<Environment>>) -> Self {
-        Self { values: RefCell::new(HashMap::new()), enclosing }
+        Self { values: RefCell::new(HashMap::new()), constants: RefCell::new(HashSet::new()), enclosing }
     }
 
     pub fn define(&self, name: String, value: TokenLiteral) {
         self.values.borrow_mut().insert(name, value);
     }
 
+    pub fn define_const(&self, name: String, value: TokenLiteral) {
+        self.constants.borrow_mut().insert(name.clone());
+        self.define(name, value);
+    }
+
+    pub fn is_constant(&self, name: &str) -> bool {
+        self.constants.borrow().contains(name)
+    }
+
     pub fn get(&self, name: &Token) -> Result<TokenLiteral, InterpreterError> {
         match self.values.borrow().get(&name.lexeme) {
             Some(val) => Ok(val.clone()),
@@ -56,6 +75,10 @@ impl Environment {
     }
 
     pub fn assign(&self, name: &Token, value: TokenLiteral) -> Result<(), InterpreterError> {
+        if self.is_constant(&name.lexeme) {
+            let err_msg = format!("Can't assign to constant '{}'.", &name.lexeme);
+            return Err(InterpreterError::OperatorError { line: name.line, err_msg });
+        }
         match self.values.borrow_mut().get_mut(&name.lexeme) {
             Some(val) => {
                 *val = value;
@@ -80,9 +103,94 @@ impl Environment {
         self.ancestor(distance).deref().assign(name, value)
     }
 
+    /// Registers a host function implemented by a Rust closure
+    /// Closures report errors with `InterpreterError::NativeError`, which gets the line of the call attached
+    pub fn define_native<F>(&self, name: &str, arity: Arity, function: F)
+    where
+        F: Fn(&mut Interpreter, Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> + 'static,
+    {
+        let host = HostFunction::new(String::from(name), arity, Box::new(function));
+        self.define(String::from(name), TokenLiteral::LOX_CALLABLE(Rc::new(LoxCallable::Native(NativeFunction::Host(host)))));
+    }
+
+    /// Registers a class implemented by the host, see `LoxClass::new_native`
+    pub fn define_native_class(&self, class: LoxClass) {
+        let name = class.to_string();
+        self.define(name, TokenLiteral::LOX_CALLABLE(Rc::new(LoxCallable::ClassConstructor(Rc::new(class)))));
+    }
+
+    /// The arity of every native function, for checking calls without running them
+    pub fn native_arities() -> HashMap<String, Arity> {
+        let natives = Environment::new(None);
+        natives.init_native_funcs();
+        let values = natives.values.borrow();
+        values.iter().filter_map(|(name, value)| match value {
+            TokenLiteral::LOX_CALLABLE(callable) => Some((name.clone(), callable.arity())),
+            _ => None,
+        }).collect()
+    }
+
     pub fn init_native_funcs(&self) {
         // Native functions are extensible via implementing the LoxCallable trait object on them
         // Clock
-        self.define(String::from("clock"),TokenLiteral::LOX_CALLABLE(Rc::new(LoxCallable::Native(NativeFunction::NativeClock(Clock)))));
+        self.define_native("clock", Arity::Exact(0), |_, _| Clock::time_since_epoch_as_secs());
+        for native in TimeNative::ALL {
+            self.define_native(native.name(), Arity::Exact(native.arity()), move |interpreter, arguments| native.call(interpreter, arguments));
+        }
+        // Numeric conversions
+        self.define_native("int", Arity::Exact(1), |_, arguments| Int::call(arguments));
+        self.define_native("float", Arity::Exact(1), |_, arguments| Float::call(arguments));
+        // Other conversions and introspection
+        self.define_native("str", Arity::Exact(1), |_, arguments| Str::call(arguments));
+        self.define_native("num", Arity::Exact(1), |_, arguments| Num::call(arguments));
+        self.define_native("bool", Arity::Exact(1), |_, arguments| Bool::call(arguments));
+        self.define_native("typeof", Arity::Exact(1), |_, arguments| TypeOf::call(arguments));
+        // Math
+        for native in MathNative::ALL {
+            self.define_native(native.name(), native.arity(), move |_, arguments| native.call(arguments));
+        }
+        // Plain globals rather than constants, so scripts are free to reuse short names like `E`
+        for (name, value) in MathNative::CONSTANTS {
+            self.define(String::from(name), TokenLiteral::LOX_NUMBER(value));
+        }
+        // Random numbers
+        for native in RandomNative::ALL {
+            self.define_native(native.name(), Arity::Exact(native.arity()), move |interpreter, arguments| native.call(interpreter, arguments));
+        }
+        // Files
+        for native in FileNative::ALL {
+            self.define_native(native.name(), Arity::Exact(native.arity()), move |interpreter, arguments| native.call(interpreter, arguments));
+        }
+        // Process
+        for native in ProcessNative::ALL {
+            self.define_native(native.name(), Arity::Exact(native.arity()), move |_, arguments| native.call(arguments));
+        }
+        // JSON
+        for native in JsonNative::ALL {
+            self.define_native(native.name(), Arity::Exact(native.arity()), move |_, arguments| native.call(arguments));
+        }
+        // Assertions
+        for native in AssertNative::ALL {
+            self.define_native(native.name(), Arity::Exact(native.arity()), move |_, arguments| native.call(arguments));
+        }
+        // Classes
+        self.define_native("instanceOf", Arity::Exact(2), |_, arguments| {
+            let class = match &arguments[1] {
+                TokenLiteral::LOX_CALLABLE(callable) => match callable.deref() {
+                    LoxCallable::ClassConstructor(class) => class,
+                    _ => return Err(InterpreterError::NativeError(format!("instanceOf() expects a class but got {callable}."))),
+                },
+                other => return Err(InterpreterError::NativeError(format!("instanceOf() expects a class but got {other}."))),
+            };
+            match &arguments[0] {
+                TokenLiteral::LOX_INSTANCE(instance) => Ok(TokenLiteral::LOX_BOOL(instance.class.is_subclass_of(class))),
+                _ => Ok(TokenLiteral::LOX_BOOL(false)),
+            }
+        });
+        // Strings
+        self.define_native_class(string_builder_class());
+        for native in StringNative::ALL {
+            self.define_native(native.name(), Arity::Exact(native.arity()), move |_, arguments| native.call(arguments));
+        }
     }
 }
\ No newline at end of file
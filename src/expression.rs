@@ -30,6 +30,17 @@ pub enum Expr {
         expression: Box<Expr>,
     },
 
+    Index {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+    },
+
+    List {
+        bracket: Token,
+        elements: Vec<Expr>,
+    },
+
     Literal {
         value: TokenLiteral,
     },
@@ -40,6 +51,11 @@ pub enum Expr {
         right: Box<Expr>,
     },
 
+    Map {
+        brace: Token,
+        entries: Vec<(Expr, Expr)>,
+    },
+
     Set {
         object: Box<Expr>,
         name: Token,
@@ -47,6 +63,13 @@ pub enum Expr {
         id: usize,
     },
 
+    SetIndex {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        value: Box<Expr>,
+    },
+
     Super {
         keyword: Token,
         method: Token,
@@ -63,6 +86,12 @@ pub enum Expr {
         right: Box<Expr>,
     },
 
+    Unpack {
+        bracket: Token,
+        targets: Vec<Expr>,
+        value: Box<Expr>,
+    },
+
     Variable {
         name: Token,
         id: usize,
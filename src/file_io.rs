@@ -0,0 +1,135 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::token_literal::TokenLiteral::{self, LOX_BOOL, LOX_LIST, LOX_NULL, LOX_STRING};
+
+// File and console input natives
+// They fail unless the interpreter has been granted filesystem access, as scripts are otherwise sandboxed
+#[derive(Clone, Copy)]
+pub enum FileNative {
+    ReadFile,
+    WriteFile,
+    AppendFile,
+    ReadLines,
+    FileExists,
+    ListDir,
+    ReadLine,
+}
+
+impl FileNative {
+    pub const ALL: [FileNative; 7] = [
+        FileNative::ReadFile,
+        FileNative::WriteFile,
+        FileNative::AppendFile,
+        FileNative::ReadLines,
+        FileNative::FileExists,
+        FileNative::ListDir,
+        FileNative::ReadLine,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileNative::ReadFile => "readFile",
+            FileNative::WriteFile => "writeFile",
+            FileNative::AppendFile => "appendFile",
+            FileNative::ReadLines => "readLines",
+            FileNative::FileExists => "fileExists",
+            FileNative::ListDir => "listDir",
+            FileNative::ReadLine => "readLine",
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            FileNative::ReadLine => 0,
+            FileNative::ReadFile | FileNative::ReadLines | FileNative::FileExists | FileNative::ListDir => 1,
+            FileNative::WriteFile | FileNative::AppendFile => 2,
+        }
+    }
+
+    pub fn call(&self, interpreter: &mut Interpreter, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        if !interpreter.allow_fs {
+            return Err(self.error(String::from("filesystem access is disabled; run with --allow-fs to enable it.")));
+        }
+
+        match self {
+            FileNative::ReadFile => {
+                let path = self.path(&arguments)?;
+                let contents = fs::read_to_string(path).map_err(|err| self.io_error(path, err))?;
+                Ok(FileNative::new_string(contents))
+            }
+            FileNative::WriteFile => {
+                let path = self.path(&arguments)?;
+                fs::write(path, self.text(&arguments)?).map_err(|err| self.io_error(path, err))?;
+                Ok(LOX_NULL)
+            }
+            FileNative::AppendFile => {
+                let path = self.path(&arguments)?;
+                let mut file = OpenOptions::new().create(true).append(true).open(path).map_err(|err| self.io_error(path, err))?;
+                file.write_all(self.text(&arguments)?.as_bytes()).map_err(|err| self.io_error(path, err))?;
+                Ok(LOX_NULL)
+            }
+            FileNative::ReadLines => {
+                let path = self.path(&arguments)?;
+                let contents = fs::read_to_string(path).map_err(|err| self.io_error(path, err))?;
+                let lines: Vec<TokenLiteral> = contents.lines().map(|line| FileNative::new_string(line.to_string())).collect();
+                Ok(LOX_LIST(Rc::new(lines.into())))
+            }
+            FileNative::FileExists => Ok(LOX_BOOL(Path::new(self.path(&arguments)?).exists())),
+            FileNative::ListDir => {
+                let path = self.path(&arguments)?;
+                let mut names = Vec::new();
+                for entry in fs::read_dir(path).map_err(|err| self.io_error(path, err))? {
+                    let entry = entry.map_err(|err| self.io_error(path, err))?;
+                    names.push(entry.file_name().to_string_lossy().into_owned());
+                }
+                // Directory order depends on the platform, so sort for reproducible scripts
+                names.sort();
+                let names: Vec<TokenLiteral> = names.into_iter().map(FileNative::new_string).collect();
+                Ok(LOX_LIST(Rc::new(names.into())))
+            }
+            // nil once stdin is exhausted
+            FileNative::ReadLine => {
+                let mut line = String::new();
+                match io::stdin().read_line(&mut line) {
+                    Ok(0) => Ok(LOX_NULL),
+                    Ok(_) => {
+                        let trimmed = line.trim_end_matches(['\n', '\r']).len();
+                        line.truncate(trimmed);
+                        Ok(FileNative::new_string(line))
+                    }
+                    Err(err) => Err(self.error(format!("could not read from stdin: {err}."))),
+                }
+            }
+        }
+    }
+
+    fn new_string(string: String) -> TokenLiteral {
+        LOX_STRING(Rc::new(string))
+    }
+
+    fn error(&self, message: String) -> InterpreterError {
+        InterpreterError::NativeError(format!("{}() {message}", self.name()))
+    }
+
+    fn io_error(&self, path: &str, err: io::Error) -> InterpreterError {
+        self.error(format!("failed on '{path}': {err}."))
+    }
+
+    fn path<'a>(&self, arguments: &'a [TokenLiteral]) -> Result<&'a str, InterpreterError> {
+        match &arguments[0] {
+            LOX_STRING(path) => Ok(path.as_str()),
+            other => Err(self.error(format!("expects a path string but got {other}."))),
+        }
+    }
+
+    fn text<'a>(&self, arguments: &'a [TokenLiteral]) -> Result<&'a str, InterpreterError> {
+        match &arguments[1] {
+            LOX_STRING(text) => Ok(text.as_str()),
+            other => Err(self.error(format!("expects a string to write but got {other}."))),
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use std::fs;
+
+use crate::lox;
+use crate::parser::Parser;
+use crate::scanner::Scanner;
+use crate::token::Token;
+use crate::token_type::TokenType::{self, *};
+
+const INDENT: &str = "  ";
+// Lines longer than this are wrapped at the commas of their outermost bracketed list
+const MAX_WIDTH: usize = 100;
+
+/// Formats the given files in place, or with `check` only reports the ones that aren't formatted
+/// Files that don't parse are left alone; returns the process exit code
+pub fn run_fmt(paths: &[String], check: bool) -> i32 {
+    let (mut unformatted, mut broken) = (0, 0);
+    for file in lox::lox_files(paths) {
+        let source = match fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("{}: {err}", file.display());
+                broken += 1;
+                continue;
+            }
+        };
+        let Some(formatted) = format_source(&source) else {
+            eprintln!("{}: not formatted because of syntax errors", file.display());
+            broken += 1;
+            continue;
+        };
+        if formatted == source {
+            continue;
+        }
+
+        if check {
+            println!("{} is not formatted", file.display());
+            unformatted += 1;
+        } else if let Err(err) = fs::write(&file, formatted) {
+            eprintln!("{}: {err}", file.display());
+            broken += 1;
+        } else {
+            println!("formatted {}", file.display());
+        }
+    }
+
+    match (broken, unformatted) {
+        (0, 0) => 0,
+        (0, _) => 1,
+        _ => 65,
+    }
+}
+
+/// The canonical layout of the source, or None if it doesn't parse
+/// Only whitespace changes: comments are kept, and a single blank line is kept wherever the source had any
+pub fn format_source(source: &str) -> Option<String> {
+    lox::clear_error();
+    let tokens = Scanner::new(source.to_string()).scan_tokens();
+    // Some errors, like an invalid assignment target, are reported without failing the parse
+    let parsed = Parser::new(tokens).parse();
+    if lox::had_error() || parsed.is_err() {
+        return None;
+    }
+
+    let tokens = Scanner::with_comments(source.to_string()).scan_tokens();
+    let mut formatter = Formatter::new(&tokens);
+    formatter.format();
+
+    let mut out = String::new();
+    for line in formatter.lines {
+        line.wrap(&mut out);
+    }
+    Some(out)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Group {
+    // ( [ and the braces of map literals and destructuring, whose contents can be wrapped
+    Open,
+    Close,
+    Comma,
+    // Unary ! and -, which stick to their operand
+    Prefix,
+    Other,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Brace {
+    Block,
+    // Map literals and destructuring, laid out like lists
+    Inline,
+    // Enum bodies, one variant per line
+    Enum,
+}
+
+struct Piece {
+    text: String,
+    space_before: bool,
+    group: Group,
+}
+
+// One line of output; a line without pieces or a comment is blank
+#[derive(Default)]
+struct Line {
+    indent: usize,
+    pieces: Vec<Piece>,
+    comment: Option<String>,
+}
+
+impl Line {
+    fn is_blank(&self) -> bool {
+        self.pieces.is_empty() && self.comment.is_none()
+    }
+
+    fn render(&self) -> String {
+        if self.is_blank() {
+            return String::new();
+        }
+        let mut text = INDENT.repeat(self.indent);
+        for (i, piece) in self.pieces.iter().enumerate() {
+            if i > 0 && piece.space_before {
+                text.push(' ');
+            }
+            text.push_str(&piece.text);
+        }
+        if let Some(comment) = &self.comment {
+            if !self.pieces.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(comment);
+        }
+        text
+    }
+
+    // Splits an overlong line at the commas of its outermost bracketed list, one element per line
+    fn wrap(self, out: &mut String) {
+        let rendered = self.render();
+        let Some((open, close)) = self.split_point().filter(|_| rendered.chars().count() > MAX_WIDTH) else {
+            out.push_str(&rendered);
+            out.push('\n');
+            return;
+        };
+
+        let indent = self.indent;
+        let mut pieces = self.pieces.into_iter();
+        let head: Vec<Piece> = pieces.by_ref().take(open + 1).collect();
+        Line { indent, pieces: head, comment: None }.wrap(out);
+
+        let mut element = Line { indent: indent + 1, ..Line::default() };
+        let mut depth = 0;
+        for _ in open + 1..close {
+            let mut piece = pieces.next().unwrap();
+            if element.pieces.is_empty() {
+                piece.space_before = false;
+            }
+            match piece.group {
+                Group::Open => depth += 1,
+                Group::Close => depth -= 1,
+                _ => (),
+            }
+            let ends_element = piece.group == Group::Comma && depth == 0;
+            element.pieces.push(piece);
+            if ends_element {
+                std::mem::take(&mut element).wrap(out);
+                element.indent = indent + 1;
+            }
+        }
+        if !element.pieces.is_empty() {
+            element.wrap(out);
+        }
+
+        let mut tail: Vec<Piece> = pieces.collect();
+        tail[0].space_before = false;
+        Line { indent, pieces: tail, comment: self.comment }.wrap(out);
+    }
+
+    // The first bracket whose matching close is on this line and which directly holds a comma
+    fn split_point(&self) -> Option<(usize, usize)> {
+        for (open, piece) in self.pieces.iter().enumerate() {
+            if piece.group != Group::Open {
+                continue;
+            }
+            let mut depth = 0;
+            let mut has_comma = false;
+            for (i, inner) in self.pieces.iter().enumerate().skip(open + 1) {
+                match inner.group {
+                    Group::Open => depth += 1,
+                    Group::Close if depth == 0 => {
+                        if has_comma {
+                            return Some((open, i));
+                        }
+                        break;
+                    }
+                    Group::Close => depth -= 1,
+                    Group::Comma if depth == 0 => has_comma = true,
+                    _ => (),
+                }
+            }
+        }
+        None
+    }
+}
+
+struct Formatter<'a> {
+    tokens: &'a [Token],
+    lines: Vec<Line>,
+    current: Line,
+    indent: usize,
+    // Set after tokens that end a line, the break only happens once the next token shows up
+    // so that a trailing comment can still join the line
+    pending_break: bool,
+    braces: Vec<Brace>,
+    // Open parens, brackets and inline braces; semicolons and commas inside them don't end lines
+    nesting: usize,
+    // Indices of the last two tokens that aren't comments
+    prev: Option<usize>,
+    before_prev: Option<usize>,
+    // Whether the previous token ends an operand, which makes a following `-` binary
+    prev_is_value: bool,
+    // Source line the previous token or comment ended on, to find the blank lines worth keeping
+    last_line: i32,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(tokens: &'a [Token]) -> Self {
+        Self {
+            tokens,
+            lines: Vec::new(),
+            current: Line::default(),
+            indent: 0,
+            pending_break: false,
+            braces: Vec::new(),
+            nesting: 0,
+            prev: None,
+            before_prev: None,
+            prev_is_value: false,
+            last_line: 0,
+        }
+    }
+
+    fn format(&mut self) {
+        for (i, token) in self.tokens.iter().enumerate() {
+            match token.token_type {
+                EOF => break,
+                COMMENT => self.comment(token),
+                _ => self.token(i),
+            }
+        }
+        self.break_line();
+    }
+
+    fn comment(&mut self, comment: &Token) {
+        let text = String::from(comment.lexeme.trim_end());
+        if comment.line == self.last_line && !self.current.pieces.is_empty() {
+            self.current.comment = Some(text);
+        } else {
+            self.break_line();
+            self.blank_line_if_gap(comment.line, false);
+            self.lines.push(Line { indent: self.indent, pieces: Vec::new(), comment: Some(text) });
+        }
+        self.pending_break = true;
+        self.last_line = comment.line;
+    }
+
+    fn token(&mut self, index: usize) {
+        let token = &self.tokens[index];
+        let prev = self.prev.map(|prev| self.tokens[prev].token_type);
+
+        let brace = match token.token_type {
+            LEFT_BRACE => Some(self.brace_kind()),
+            RIGHT_BRACE => Some(self.braces.pop().unwrap_or(Brace::Block)),
+            _ => None,
+        };
+        let closes_block = token.token_type == RIGHT_BRACE && brace != Some(Brace::Inline);
+        // An empty block stays as `{}` on the line that opened it
+        let closes_empty_block = closes_block && prev == Some(LEFT_BRACE) && self.current.comment.is_none() && !self.current.pieces.is_empty();
+        if closes_block {
+            self.indent = self.indent.saturating_sub(1);
+            // Enum bodies may end without a comma or semicolon to break the line
+            self.pending_break = !closes_empty_block;
+        }
+
+        let joins_previous = closes_empty_block
+            || (matches!(token.token_type, ELSE | CATCH) && prev == Some(RIGHT_BRACE) && self.current.comment.is_none());
+        if self.pending_break && !joins_previous {
+            self.break_line();
+            self.blank_line_if_gap(token.line, closes_block);
+        }
+        self.pending_break = false;
+        if self.current.pieces.is_empty() && self.current.comment.is_none() {
+            self.current.indent = self.indent;
+        }
+
+        let is_prefix = token.token_type == BANG || (token.token_type == MINUS && !self.prev_is_value);
+        let group = match token.token_type {
+            LEFT_PAREN | LEFT_BRACKET => Group::Open,
+            RIGHT_PAREN | RIGHT_BRACKET => Group::Close,
+            LEFT_BRACE if brace == Some(Brace::Inline) => Group::Open,
+            RIGHT_BRACE if brace == Some(Brace::Inline) => Group::Close,
+            COMMA => Group::Comma,
+            _ if is_prefix => Group::Prefix,
+            _ => Group::Other,
+        };
+        let space_before = !closes_empty_block && self.needs_space(token.token_type, prev, group);
+        self.current.pieces.push(Piece { text: token.lexeme.clone(), space_before, group });
+
+        match token.token_type {
+            LEFT_PAREN | LEFT_BRACKET => self.nesting += 1,
+            RIGHT_PAREN | RIGHT_BRACKET => self.nesting = self.nesting.saturating_sub(1),
+            LEFT_BRACE => {
+                let brace = brace.unwrap();
+                self.braces.push(brace);
+                if brace == Brace::Inline {
+                    self.nesting += 1;
+                } else {
+                    self.indent += 1;
+                    self.pending_break = true;
+                }
+            }
+            RIGHT_BRACE if brace == Some(Brace::Inline) => self.nesting = self.nesting.saturating_sub(1),
+            RIGHT_BRACE => self.pending_break = true,
+            SEMICOLON if self.nesting == 0 => self.pending_break = true,
+            COMMA if self.nesting == 0 && self.braces.last() == Some(&Brace::Enum) => self.pending_break = true,
+            _ => (),
+        }
+
+        self.prev_is_value = matches!(token.token_type, IDENTIFIER | NUMBER | STRING | TRUE | FALSE | NIL | THIS | RIGHT_PAREN | RIGHT_BRACKET)
+            || (token.token_type == RIGHT_BRACE && brace == Some(Brace::Inline));
+        self.before_prev = self.prev;
+        self.prev = Some(index);
+        self.last_line = token.line + token.lexeme.matches('\n').count() as i32;
+    }
+
+    // Braces in expression position hold a map literal or a destructuring pattern, anything else opens a body
+    fn brace_kind(&self) -> Brace {
+        let type_at = |index: Option<usize>| index.map(|index| self.tokens[index].token_type);
+        match type_at(self.prev) {
+            Some(IDENTIFIER) if type_at(self.before_prev) == Some(ENUM) => Brace::Enum,
+            Some(LEFT_BRACE) if self.braces.last() == Some(&Brace::Inline) => Brace::Inline,
+            Some(
+                EQUAL | LEFT_PAREN | COMMA | LEFT_BRACKET | COLON | RETURN | PRINT | YIELD | IN | VAR | CONST | PLUS | MINUS | STAR | SLASH
+                | BANG | EQUAL_EQUAL | BANG_EQUAL | GREATER | GREATER_EQUAL | LESS | LESS_EQUAL | AND | OR,
+            ) => Brace::Inline,
+            _ => Brace::Block,
+        }
+    }
+
+    fn needs_space(&self, token_type: TokenType, prev: Option<TokenType>, group: Group) -> bool {
+        let Some(last) = self.current.pieces.last() else {
+            return false;
+        };
+        if matches!(last.group, Group::Open | Group::Prefix) || last.text == "." {
+            return false;
+        }
+        match token_type {
+            SEMICOLON | COMMA | DOT | COLON | RIGHT_PAREN | RIGHT_BRACKET => false,
+            RIGHT_BRACE => group != Group::Close,
+            // Calls and indexing hug what they apply to, unlike `if (` or `= [`
+            LEFT_PAREN => !matches!(prev, Some(IDENTIFIER | THIS | RIGHT_PAREN | RIGHT_BRACKET)),
+            LEFT_BRACKET => !matches!(prev, Some(IDENTIFIER | THIS | STRING | RIGHT_PAREN | RIGHT_BRACKET)),
+            _ => true,
+        }
+    }
+
+    fn break_line(&mut self) {
+        if !self.current.pieces.is_empty() || self.current.comment.is_some() {
+            self.lines.push(std::mem::take(&mut self.current));
+        }
+    }
+
+    // Keeps one blank line where the source had some, except at the edges of a block
+    fn blank_line_if_gap(&mut self, line: i32, closes_block: bool) {
+        let Some(last) = self.lines.last() else {
+            return;
+        };
+        let opens_block = last.comment.is_none() && last.pieces.last().is_some_and(|piece| piece.text == "{" && piece.group != Group::Open);
+        if line > self.last_line + 1 && !last.is_blank() && !opens_block && !closes_block {
+            self.lines.push(Line::default());
+        }
+    }
+}
@@ -7,6 +7,7 @@ use crate::environment::Environment;
 use crate::interpreter::{Interpreter, InterpreterError};
 use crate::statement::Stmt;
 use crate::function_object::FunctionObject;
+use crate::generator::Generator;
 use crate::token::Token;
 use crate::token_literal::TokenLiteral;
 use crate::token_type::TokenType;
@@ -32,15 +33,21 @@ impl LoxFunction {
             Stmt::Function { ptr } => {
                 let FunctionObject { params, body , .. } = ptr.as_ref();
                 let environment = Environment::new(Some(Rc::clone(&self.closure)));
-                for (param_name, value) in zip(params.iter(), arguments.into_iter()) {
+                for (param_name, value) in zip(params.iter(), arguments) {
                     environment.define(param_name.lexeme.clone(), value);
                 }
+
+                // Generator bodies only start running once the generator is resumed
+                if ptr.is_generator {
+                    let generator = Generator::new(Rc::clone(ptr), Rc::new(environment));
+                    return Ok(TokenLiteral::LOX_GENERATOR(Rc::new(generator)));
+                }
                 let block_return_val = interpreter.execute_block(body, Rc::new(environment));
 
                 // Force-return `this` if calling constructor
                 if self.is_initializer {
                     // get_at takes a &Token, but we only care that its lexeme is 'this'
-                    let dummy_token = Token { token_type: TokenType::EOF, lexeme: String::from("this"), line: 0, literal: TokenLiteral::LOX_NULL };
+                    let dummy_token = Token { token_type: TokenType::EOF, lexeme: String::from("this"), line: 0, literal: TokenLiteral::LOX_NULL, column: 0 };
                     return self.closure.get_at(0, &dummy_token);
                 }
 
@@ -4,5 +4,7 @@ use crate::token::Token;
 pub struct FunctionObject {
     pub name: Token,
     pub params: Vec<Token>,
-    pub body: Vec<Stmt>
+    pub body: Vec<Stmt>,
+    // Set by the parser when the body contains a `yield`
+    pub is_generator: bool,
 }
\ No newline at end of file
@@ -0,0 +1,285 @@
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use std::slice;
+
+use crate::environment::Environment;
+use crate::function_object::FunctionObject;
+use crate::interpreter::{Interpreter, InterpreterError, IterationSource};
+use crate::statement::Stmt;
+use crate::token_literal::TokenLiteral;
+
+// The tree-walking interpreter executes blocks recursively, which leaves no way to pause halfway through one.
+// A generator instead walks its function body with an explicit stack of frames, so that the whole
+// position within the body can be stored away at a `yield` and picked up again on the next resume.
+pub struct Generator {
+    function: Rc<FunctionObject>,
+    state: RefCell<GeneratorState>,
+}
+
+enum GeneratorState {
+    Suspended(Vec<Frame>),
+    Running,
+    Done,
+}
+
+// A list of statements being executed, e.g. a block or a loop body
+struct Frame {
+    source: FrameSource,
+    env: Rc<Environment>,
+    // Index of the next statement to execute
+    next: usize,
+    repeat: Repeat,
+}
+
+// Where a frame's statements live, relative to the statement of the enclosing frame that pushed it
+// Frames can't borrow the statements directly, as they must outlive the borrow of the function body
+enum FrameSource {
+    Body,
+    Block,
+    Then,
+    Else,
+    Loop,
+    Arm(usize),
+    Try,
+    Catch,
+}
+
+// What happens once a frame runs out of statements
+enum Repeat {
+    Once,
+    While,
+    ForIn { name: String, source: IterationSource, line: i32 },
+}
+
+impl Frame {
+    fn new(source: FrameSource, env: Rc<Environment>, repeat: Repeat) -> Self {
+        Self { source, env, next: 0, repeat }
+    }
+}
+
+impl Generator {
+    pub fn new(function: Rc<FunctionObject>, environment: Rc<Environment>) -> Self {
+        let frames = vec![Frame::new(FrameSource::Body, environment, Repeat::Once)];
+        Self { function, state: RefCell::new(GeneratorState::Suspended(frames)) }
+    }
+
+    /// Runs the generator up to its next `yield`, returning `None` once the body has finished
+    pub fn resume(&self, interpreter: &mut Interpreter) -> Result<Option<TokenLiteral>, InterpreterError> {
+        let mut frames = match self.state.replace(GeneratorState::Running) {
+            GeneratorState::Suspended(frames) => frames,
+            GeneratorState::Running => {
+                let err_msg = format!("Generator '{}' is already running.", self.function.name.lexeme);
+                return Err(InterpreterError::OperatorError { line: self.function.name.line, err_msg });
+            }
+            GeneratorState::Done => {
+                self.state.replace(GeneratorState::Done);
+                return Ok(None);
+            }
+        };
+
+        let previous = Rc::clone(&interpreter.curr_env);
+        let result = self.run(interpreter, &mut frames);
+        interpreter.curr_env = previous;
+
+        // Errors end the generator just like reaching the end of its body
+        match result {
+            Ok(Some(value)) => {
+                self.state.replace(GeneratorState::Suspended(frames));
+                Ok(Some(value))
+            }
+            Ok(None) => {
+                self.state.replace(GeneratorState::Done);
+                Ok(None)
+            }
+            Err(error) => {
+                self.state.replace(GeneratorState::Done);
+                Err(error)
+            }
+        }
+    }
+
+    /// Whether the body has finished, so the last `next()` returned no yielded value
+    pub fn is_done(&self) -> bool {
+        matches!(*self.state.borrow(), GeneratorState::Done)
+    }
+
+    fn run(&self, interpreter: &mut Interpreter, frames: &mut Vec<Frame>) -> Result<Option<TokenLiteral>, InterpreterError> {
+        loop {
+            match self.run_until_error(interpreter, frames) {
+                Err(InterpreterError::OperatorError { err_msg, line }) => {
+                    // Runtime errors unwind to the innermost enclosing try, whose catch block runs in its place
+                    let Some(depth) = frames.iter().rposition(|frame| matches!(frame.source, FrameSource::Try)) else {
+                        return Err(InterpreterError::OperatorError { err_msg, line });
+                    };
+                    frames.truncate(depth);
+                    let Stmt::Try { name, .. } = Generator::owner(&self.function.body, frames, depth) else {
+                        unreachable!("Try frames are only pushed by try statements")
+                    };
+                    interpreter.curr_env = Rc::clone(&frames[depth - 1].env);
+                    let catch_env = Rc::new(interpreter.catch_env(name, err_msg));
+                    frames.push(Frame::new(FrameSource::Catch, catch_env, Repeat::Once));
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn run_until_error(&self, interpreter: &mut Interpreter, frames: &mut Vec<Frame>) -> Result<Option<TokenLiteral>, InterpreterError> {
+        let root = &self.function.body;
+        while let Some(depth) = frames.len().checked_sub(1) {
+            let statements = Generator::statements(root, frames, depth);
+            let frame = &mut frames[depth];
+            interpreter.curr_env = Rc::clone(&frame.env);
+
+            if frame.next >= statements.len() {
+                self.finish_frame(interpreter, frames, depth)?;
+                continue;
+            }
+
+            let stmt = &statements[frame.next];
+            frame.next += 1;
+            let env = Rc::clone(&frame.env);
+
+            // Statements that contain other statements push a frame rather than running them recursively
+            match stmt {
+                Stmt::Yield { value, .. } => return Ok(Some(interpreter.accept_expr(value)?)),
+                Stmt::Block { .. } => {
+                    let block_env = Rc::new(Environment::new(Some(env)));
+                    frames.push(Frame::new(FrameSource::Block, block_env, Repeat::Once));
+                }
+                Stmt::If { expression, .. } => {
+                    let source = match Interpreter::is_truthy(&interpreter.accept_expr(expression)?) {
+                        true => FrameSource::Then,
+                        false => FrameSource::Else,
+                    };
+                    frames.push(Frame::new(source, env, Repeat::Once));
+                }
+                Stmt::While { expression, .. } => {
+                    if Interpreter::is_truthy(&interpreter.accept_expr(expression)?) {
+                        frames.push(Frame::new(FrameSource::Loop, env, Repeat::While));
+                    }
+                }
+                Stmt::ForIn { keyword, name, iterable, .. } => {
+                    let iterable = interpreter.accept_expr(iterable)?;
+                    let mut source = interpreter.iteration_source(iterable, keyword.line)?;
+                    if let Some(item) = interpreter.next_item(&mut source, keyword.line)? {
+                        let loop_env = Generator::loop_env(&env, &name.lexeme, item);
+                        let repeat = Repeat::ForIn { name: name.lexeme.clone(), source, line: keyword.line };
+                        frames.push(Frame::new(FrameSource::Loop, loop_env, repeat));
+                    }
+                }
+                Stmt::Match { subject, arms, .. } => {
+                    let value = interpreter.accept_expr(subject)?;
+                    if let Some((i, arm_env)) = interpreter.select_arm(arms, &value)? {
+                        frames.push(Frame::new(FrameSource::Arm(i), arm_env, Repeat::Once));
+                    }
+                }
+                Stmt::Try { .. } => frames.push(Frame::new(FrameSource::Try, env, Repeat::Once)),
+                Stmt::Break { .. } => Generator::unwind(frames, true),
+                Stmt::Continue { .. } => Generator::unwind(frames, false),
+                Stmt::Return { value, .. } => {
+                    // The resolver only allows a bare `return;`, which simply ends the generator
+                    interpreter.accept_expr(value)?;
+                    return Ok(None);
+                }
+                _ => match interpreter.accept_statement(stmt) {
+                    Ok(_) => (),
+                    Err(InterpreterError::Break) => Generator::unwind(frames, true),
+                    Err(InterpreterError::Continue) => Generator::unwind(frames, false),
+                    Err(InterpreterError::Return(_)) => return Ok(None),
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Handles a frame that has run out of statements: loops go around again, everything else is popped
+    fn finish_frame(&self, interpreter: &mut Interpreter, frames: &mut Vec<Frame>, depth: usize) -> Result<(), InterpreterError> {
+        if depth == 0 {
+            // The function body itself has finished
+            frames.pop();
+            return Ok(());
+        }
+
+        let owner = Generator::owner(&self.function.body, frames, depth);
+        let parent_env = Rc::clone(&frames[depth - 1].env);
+        let frame = &mut frames[depth];
+
+        let again = match &mut frame.repeat {
+            Repeat::Once => false,
+            Repeat::While => {
+                let Stmt::While { expression, increment, .. } = owner else {
+                    unreachable!("While frames are only pushed by while statements")
+                };
+                if let Some(increment) = increment {
+                    interpreter.accept_expr(increment)?;
+                }
+                Interpreter::is_truthy(&interpreter.accept_expr(expression)?)
+            }
+            Repeat::ForIn { name, source, line } => match interpreter.next_item(source, *line)? {
+                Some(item) => {
+                    // Every iteration gets a fresh environment for the loop variable
+                    frame.env = Generator::loop_env(&parent_env, name, item);
+                    true
+                }
+                None => false,
+            }
+        };
+
+        match again {
+            true => frame.next = 0,
+            false => { frames.pop(); }
+        }
+        Ok(())
+    }
+
+    /// Pops frames up to the innermost loop, which is then either exited or sent around again
+    fn unwind(frames: &mut Vec<Frame>, is_break: bool) {
+        while let Some(frame) = frames.last_mut() {
+            if !matches!(frame.repeat, Repeat::Once) {
+                match is_break {
+                    true => { frames.pop(); }
+                    false => frame.next = usize::MAX,
+                }
+                return;
+            }
+            frames.pop();
+        }
+    }
+
+    fn loop_env(enclosing: &Rc<Environment>, name: &str, item: TokenLiteral) -> Rc<Environment> {
+        let env = Environment::new(Some(Rc::clone(enclosing)));
+        env.define(String::from(name), item);
+        Rc::new(env)
+    }
+
+    /// The statement of the enclosing frame that pushed the frame at `depth`
+    fn owner<'a>(root: &'a [Stmt], frames: &[Frame], depth: usize) -> &'a Stmt {
+        let parent = &frames[depth - 1];
+        &Generator::statements(root, frames, depth - 1)[parent.next - 1]
+    }
+
+    fn statements<'a>(root: &'a [Stmt], frames: &[Frame], depth: usize) -> &'a [Stmt] {
+        if depth == 0 {
+            return root;
+        }
+        match (&frames[depth].source, Generator::owner(root, frames, depth)) {
+            (FrameSource::Block, Stmt::Block { statements }) => statements,
+            (FrameSource::Then, Stmt::If { then_branch, .. }) => slice::from_ref(then_branch.as_ref()),
+            (FrameSource::Else, Stmt::If { else_branch, .. }) => slice::from_ref(else_branch.as_ref()),
+            (FrameSource::Loop, Stmt::While { body, .. } | Stmt::ForIn { body, .. }) => slice::from_ref(body.as_ref()),
+            (FrameSource::Arm(i), Stmt::Match { arms, .. }) => slice::from_ref(arms[*i].body.as_ref()),
+            (FrameSource::Try, Stmt::Try { body, .. }) => slice::from_ref(body.as_ref()),
+            (FrameSource::Catch, Stmt::Try { handler, .. }) => slice::from_ref(handler.as_ref()),
+            _ => unreachable!("Frame source does not match the statement that pushed it")
+        }
+    }
+}
+
+impl Display for Generator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<generator {}>", self.function.name.lexeme)
+    }
+}
\ No newline at end of file
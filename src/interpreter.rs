@@ -1,16 +1,29 @@
 use std::mem;
 use std::rc::Rc;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::iter::zip;
 use std::ops::Deref;
+use std::slice;
+use std::time::Instant;
+use std::vec;
 
+use crate::bigint::BigInt;
 use crate::callable::LoxCallable;
 use crate::class::LoxClass;
 use crate::class_instance::LoxInstance;
+use crate::enum_type::{EnumVariant, LoxEnum};
 use crate::environment::Environment;
 use crate::expression::Expr::{self, *};
 use crate::function::LoxFunction;
+use crate::generator::Generator;
 use crate::lox;
+use crate::map::LoxMap;
+use crate::native_function::NativeFunction;
+use crate::pattern::{MatchArm, Pattern};
+use crate::random::Rng;
 use crate::statement::Stmt::{self, *};
+use crate::statement::VarTarget;
 use crate::token::Token;
 use crate::token_literal::TokenLiteral;
 use crate::token_type::TokenType::*;
@@ -18,12 +31,36 @@ use crate::token_type::TokenType::*;
 pub struct Interpreter {
     pub global_env: Rc<Environment>,
     pub curr_env: Rc<Environment>,
-    pub locals: HashMap<usize, usize>
+    pub locals: HashMap<usize, usize>,
+    pub rng: Rng,
+    // Capability granted by the host, e.g. through `--allow-fs`; file natives fail without it
+    pub allow_fs: bool,
+    // Reference point for `elapsed()`
+    pub started: Instant,
+}
+
+// What a for-in loop pulls its items from
+pub enum IterationSource {
+    Items(vec::IntoIter<TokenLiteral>),
+    Iterator(Rc<LoxInstance>),
+    Generator(Rc<Generator>),
 }
 
 pub enum InterpreterError {
     OperatorError { line: i32, err_msg: String },
     Return(TokenLiteral),
+    Break,
+    Continue,
+    // Raised by native functions, which don't know the line they were called from
+    NativeError(String),
+    // Raised by `exit(code)` and never caught, so that the whole script unwinds before the process ends
+    Exit(i32),
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
@@ -31,46 +68,65 @@ impl Interpreter {
         let global = Environment::new(None);
         global.init_native_funcs();
         let global = Rc::new(global);
-        Self { curr_env: Rc::clone(&global), global_env: global, locals: HashMap::new() }
+        Self { curr_env: Rc::clone(&global), global_env: global, locals: HashMap::new(), rng: Rng::from_time(), allow_fs: false, started: Instant::now() }
     }
 
     pub fn interpret(&mut self, statements: &[Stmt]) {
         for statement in statements.iter() {
-            if let Err(error) = self.accept_statement(statement) {
-                lox::runtime_error(&error);
-                return;
+            match self.accept_statement(statement) {
+                Ok(_) => (),
+                Err(InterpreterError::Exit(code)) => {
+                    lox::request_exit(code);
+                    return;
+                }
+                Err(error) => {
+                    lox::runtime_error(&error);
+                    return;
+                }
             }
         }
     }
 
-    fn accept_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
+    pub fn accept_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
         match expr {
             Assign { .. } => self.visit_assign_expr(expr),
             Binary { .. } => self.visit_binary_expr(expr),
             Call { .. } => self.visit_call_expr(expr),
             Get { .. } => self.visit_get_expr(expr),
             Grouping { .. } => self.visit_grouping_expr(expr),
+            Index { .. } => self.visit_index_expr(expr),
+            List { .. } => self.visit_list_expr(expr),
             Literal { .. } => self.visit_literal_expr(expr),
             Logical { .. } => self.visit_logical_expr(expr),
+            Map { .. } => self.visit_map_expr(expr),
             Set { .. } => self.visit_set_expr(expr),
+            SetIndex { .. } => self.visit_set_index_expr(expr),
             Super { .. } => self.visit_super_expr(expr),
             This { .. } => self.visit_this_expr(expr),
             Unary { .. } => self.visit_unary_expr(expr),
+            Unpack { .. } => self.visit_unpack_expr(expr),
             Variable { .. } => self.visit_variable_expr(expr),
         }
     }
 
-    fn accept_statement(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
+    pub fn accept_statement(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
         match stmt {
             Block { .. } => self.visit_block_stmt(stmt),
+            Break { .. } => Err(InterpreterError::Break),
             Class { .. } => self.visit_class_stmt(stmt),
+            Continue { .. } => Err(InterpreterError::Continue),
+            Enum { .. } => self.visit_enum_stmt(stmt),
             Expression { .. } => self.visit_expression_stmt(stmt),
+            ForIn { .. } => self.visit_for_in_stmt(stmt),
             Function { .. } => self.visit_function_stmt(stmt),
+            Match { .. } => self.visit_match_stmt(stmt),
             Print { .. } => self.visit_print_stmt(stmt),
             Return { .. } => self.visit_return_stmt(stmt),
+            Try { .. } => self.visit_try_stmt(stmt),
             Var { .. } => self.visit_var_stmt(stmt),
             If { .. } => self.visit_if_stmt(stmt),
             While { .. } => self.visit_while_stmt(stmt),
+            Yield { .. } => unreachable!("Yields only appear in generator bodies, which are run by the generator"),
         }
     }
 
@@ -111,6 +167,27 @@ impl Interpreter {
         Ok(TokenLiteral::LOX_NULL)
     }
 
+    fn visit_try_stmt(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
+        match stmt {
+            Try { body, name, handler } => match self.accept_statement(body) {
+                // Only runtime errors are caught; returns, breaks and the like pass straight through
+                Err(InterpreterError::OperatorError { err_msg, .. }) => {
+                    let env = Rc::new(self.catch_env(name, err_msg));
+                    self.execute_block(slice::from_ref(handler), env)
+                }
+                result => result,
+            }
+            _ => unreachable!("Non-try statement passed to try visitor")
+        }
+    }
+
+    /// Environment holding the caught error's message for a catch block
+    pub fn catch_env(&self, name: &Token, err_msg: String) -> Environment {
+        let env = Environment::new(Some(Rc::clone(&self.curr_env)));
+        env.define(name.lexeme.clone(), TokenLiteral::LOX_STRING(Rc::new(err_msg)));
+        env
+    }
+
     fn visit_class_stmt(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
         match stmt {
             Class { name, methods, superclass } => {
@@ -131,7 +208,7 @@ impl Interpreter {
                     }
                 }?;
 
-                self.curr_env.define(name.lexeme.clone(), TokenLiteral::LOX_NULL);
+                self.define_var(name, TokenLiteral::LOX_NULL, false)?;
 
                 // let mut prev_env = None;
                 if let Some(class) = &superclass {
@@ -173,6 +250,21 @@ impl Interpreter {
         }
     }
 
+    fn visit_enum_stmt(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
+        match stmt {
+            Enum { name, variants } => {
+                let variants = variants.iter().map(|variant| EnumVariant {
+                    name: variant.name.lexeme.clone(),
+                    fields: variant.fields.iter().map(|field| field.lexeme.clone()).collect(),
+                }).collect();
+                let enum_type = LoxEnum::new(name.lexeme.clone(), variants);
+                self.define_var(name, TokenLiteral::LOX_ENUM(Rc::new(enum_type)), false)?;
+                Ok(TokenLiteral::LOX_NULL)
+            }
+            _ => unreachable!("Non-enum statement passed to enum visitor")
+        }
+    }
+
     fn visit_expression_stmt(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
         match stmt {
             Expression { expression } => {
@@ -196,15 +288,57 @@ impl Interpreter {
 
     fn visit_var_stmt(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
         match stmt {
-            Var { name, initializer } => {
+            Var { target, initializer, is_const } => {
                 let value = self.accept_expr(initializer)?;
-                self.curr_env.define(name.lexeme.clone(), value);
+                match target {
+                    VarTarget::Name(name) => self.define_var(name, value, *is_const)?,
+                    VarTarget::List { bracket, names } => {
+                        let values = Interpreter::unpack_list(bracket, &value, names.len())?;
+                        for (name, value) in zip(names.iter(), values) {
+                            self.define_var(name, value, *is_const)?;
+                        }
+                    }
+                    VarTarget::Fields { brace, names } => {
+                        for name in names.iter() {
+                            let field = match &value {
+                                TokenLiteral::LOX_INSTANCE(instance) => instance.get(Rc::clone(instance), name)?,
+                                TokenLiteral::LOX_MAP(map) => {
+                                    let key = TokenLiteral::LOX_STRING(Rc::new(name.lexeme.clone()));
+                                    let Some(field) = map.borrow().get(&key) else {
+                                        let err_msg = format!("Map has no key '{}'.", name.lexeme);
+                                        return Err(InterpreterError::OperatorError { line: name.line, err_msg });
+                                    };
+                                    field
+                                }
+                                _ => {
+                                    let err_msg = String::from("Only instances and maps can be destructured by field.");
+                                    return Err(InterpreterError::OperatorError { line: brace.line, err_msg });
+                                }
+                            };
+                            self.define_var(name, field, *is_const)?;
+                        }
+                    }
+                }
                 Ok(TokenLiteral::LOX_NULL)
             }
             _ => unreachable!("Non-var statement passed to var visitor")
         }
     }
 
+    fn define_var(&self, name: &Token, value: TokenLiteral, is_const: bool) -> Result<(), InterpreterError> {
+        // The resolver rejects redeclarations in local scopes, but globals may be redeclared freely
+        // Every declaration kind comes through here so none of them can shadow a global constant
+        if self.curr_env.is_constant(&name.lexeme) {
+            let err_msg = format!("Can't redeclare constant '{}'.", name.lexeme);
+            return Err(InterpreterError::OperatorError { line: name.line, err_msg });
+        }
+        match is_const {
+            true => self.curr_env.define_const(name.lexeme.clone(), value),
+            false => self.curr_env.define(name.lexeme.clone(), value),
+        }
+        Ok(())
+    }
+
     fn visit_if_stmt(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
         match stmt {
             If { expression, then_branch, else_branch} => {
@@ -219,9 +353,16 @@ impl Interpreter {
 
     fn visit_while_stmt(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
         match stmt {
-            While { expression, body } => {
+            While { expression, body, increment } => {
                 while Interpreter::is_truthy(&self.accept_expr(expression)?) {
-                    self.accept_statement(body)?;
+                    match self.accept_statement(body) {
+                        Err(InterpreterError::Break) => break,
+                        Err(InterpreterError::Continue) | Ok(_) => (),
+                        Err(error) => return Err(error),
+                    }
+                    if let Some(increment) = increment {
+                        self.accept_expr(increment)?;
+                    }
                 }
                 Ok(TokenLiteral::LOX_NULL)
             }
@@ -229,6 +370,189 @@ impl Interpreter {
         }
     }
 
+    fn visit_for_in_stmt(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
+        match stmt {
+            ForIn { keyword, name, iterable, body } => {
+                let iterable = self.accept_expr(iterable)?;
+                let mut source = self.iteration_source(iterable, keyword.line)?;
+                while let Some(item) = self.next_item(&mut source, keyword.line)? {
+                    if !self.execute_for_in_body(name, item, body)? {
+                        break;
+                    }
+                }
+                Ok(TokenLiteral::LOX_NULL)
+            }
+            _ => unreachable!("Non-for-in statement passed to for-in visitor")
+        }
+    }
+
+    pub fn iteration_source(&mut self, iterable: TokenLiteral, line: i32) -> Result<IterationSource, InterpreterError> {
+        // Built-in collections are snapshotted, so the body may safely modify them
+        let items = match iterable {
+            TokenLiteral::LOX_LIST(list) => list.borrow().clone(),
+            TokenLiteral::LOX_MAP(map) => map.borrow().keys(),
+            TokenLiteral::LOX_STRING(string) => {
+                string.chars().map(|c| TokenLiteral::LOX_STRING(Rc::new(c.to_string()))).collect()
+            }
+            TokenLiteral::LOX_GENERATOR(generator) => return Ok(IterationSource::Generator(generator)),
+            TokenLiteral::LOX_INSTANCE(instance) => {
+                // Iterator protocol: `iter()` is optional, `next()` returns nil once exhausted
+                return match instance.class.find_method(&String::from("iter")) {
+                    Some(_) => match self.call_method(&instance, "iter", line)? {
+                        TokenLiteral::LOX_INSTANCE(iterator) => Ok(IterationSource::Iterator(iterator)),
+                        TokenLiteral::LOX_GENERATOR(generator) => Ok(IterationSource::Generator(generator)),
+                        _ => {
+                            let err_msg = String::from("iter() must return an iterator instance or a generator.");
+                            Err(InterpreterError::OperatorError { line, err_msg })
+                        }
+                    },
+                    None => Ok(IterationSource::Iterator(instance)),
+                };
+            }
+            _ => {
+                let err_msg = String::from("Can only iterate over lists, maps, strings, generators and iterators.");
+                return Err(InterpreterError::OperatorError { line, err_msg });
+            }
+        };
+        Ok(IterationSource::Items(items.into_iter()))
+    }
+
+    pub fn next_item(&mut self, source: &mut IterationSource, line: i32) -> Result<Option<TokenLiteral>, InterpreterError> {
+        match source {
+            IterationSource::Items(items) => Ok(items.next()),
+            IterationSource::Iterator(iterator) => match self.call_method(iterator, "next", line)? {
+                TokenLiteral::LOX_NULL => Ok(None),
+                item => Ok(Some(item)),
+            },
+            IterationSource::Generator(generator) => generator.resume(self),
+        }
+    }
+
+    /// Runs one iteration of a for-in loop, returning `false` once the loop should stop
+    fn execute_for_in_body(&mut self, name: &Token, item: TokenLiteral, body: &Stmt) -> Result<bool, InterpreterError> {
+        let env = Rc::new(Environment::new(Some(Rc::clone(&self.curr_env))));
+        env.define(name.lexeme.clone(), item);
+        match self.execute_block(slice::from_ref(body), env) {
+            Err(InterpreterError::Break) => Ok(false),
+            Err(InterpreterError::Continue) | Ok(_) => Ok(true),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn call_method(&mut self, instance: &Rc<LoxInstance>, method: &str, line: i32) -> Result<TokenLiteral, InterpreterError> {
+        let name = Token::new(IDENTIFIER, String::from(method), TokenLiteral::LOX_NULL, line, 0);
+        match instance.get(Rc::clone(instance), &name)? {
+            TokenLiteral::LOX_CALLABLE(callable) => self.call_callable(callable, Vec::new(), line),
+            _ => {
+                let err_msg = format!("'{method}' must be a method.");
+                Err(InterpreterError::OperatorError { line, err_msg })
+            }
+        }
+    }
+
+    fn visit_match_stmt(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
+        match stmt {
+            Match { subject, arms, .. } => {
+                let value = self.accept_expr(subject)?;
+                match self.select_arm(arms, &value)? {
+                    Some((i, env)) => self.execute_block(slice::from_ref(arms[i].body.as_ref()), env),
+                    None => Ok(TokenLiteral::LOX_NULL),
+                }
+            }
+            _ => unreachable!("Non-match statement passed to match visitor")
+        }
+    }
+
+    /// Finds the first arm matching `value`, along with the environment holding its bindings
+    pub fn select_arm(&mut self, arms: &[MatchArm], value: &TokenLiteral) -> Result<Option<(usize, Rc<Environment>)>, InterpreterError> {
+        for (i, arm) in arms.iter().enumerate() {
+            // Mirrors the resolver, which gives every arm its own scope
+            let env = Rc::new(Environment::new(Some(Rc::clone(&self.curr_env))));
+            if !self.match_pattern(&arm.pattern, value, &env)? {
+                continue;
+            }
+
+            if let Some(guard) = &arm.guard {
+                let previous = mem::replace(&mut self.curr_env, Rc::clone(&env));
+                let passed = self.accept_expr(guard);
+                self.curr_env = previous;
+                if !Interpreter::is_truthy(&passed?) {
+                    continue;
+                }
+            }
+
+            // First matching arm wins
+            return Ok(Some((i, env)));
+        }
+        Ok(None)
+    }
+
+    fn match_pattern(&mut self, pattern: &Pattern, value: &TokenLiteral, env: &Environment) -> Result<bool, InterpreterError> {
+        match pattern {
+            Pattern::Literal { value: literal } => Ok(Interpreter::is_equal(value.clone(), literal.clone())),
+            Pattern::Wildcard => Ok(true),
+            Pattern::Binding { name } => {
+                env.define(name.lexeme.clone(), value.clone());
+                Ok(true)
+            }
+            Pattern::Class { class, binding } => {
+                let class = match self.accept_expr(class)? {
+                    TokenLiteral::LOX_CALLABLE(callable) => match callable.deref() {
+                        LoxCallable::ClassConstructor(class) => Rc::clone(class),
+                        _ => {
+                            let err_msg = format!("'{}' in match pattern is not a class", callable);
+                            return Err(InterpreterError::OperatorError { line: binding.line, err_msg });
+                        }
+                    },
+                    other => {
+                        let err_msg = format!("'{}' in match pattern is not a class", other);
+                        return Err(InterpreterError::OperatorError { line: binding.line, err_msg });
+                    }
+                };
+
+                match value {
+                    TokenLiteral::LOX_INSTANCE(instance) if instance.class.is_subclass_of(&class) => {
+                        if binding.lexeme != "_" {
+                            env.define(binding.lexeme.clone(), value.clone());
+                        }
+                        Ok(true)
+                    }
+                    _ => Ok(false)
+                }
+            }
+            Pattern::Enum { enum_type, variant, bindings } => {
+                let enum_type = match self.accept_expr(enum_type)? {
+                    TokenLiteral::LOX_ENUM(enum_type) => enum_type,
+                    other => {
+                        let err_msg = format!("'{}' in match pattern is not an enum", other);
+                        return Err(InterpreterError::OperatorError { line: variant.line, err_msg });
+                    }
+                };
+                let Some(index) = enum_type.variant_index(&variant.lexeme) else {
+                    let err_msg = format!("Enum '{}' has no variant '{}'", enum_type.name, variant.lexeme);
+                    return Err(InterpreterError::OperatorError { line: variant.line, err_msg });
+                };
+                let fields = enum_type.variant(index).fields.len();
+                if !bindings.is_empty() && bindings.len() != fields {
+                    let err_msg = format!("Variant '{}.{}' has {} values but the pattern binds {}", enum_type.name, variant.lexeme, fields, bindings.len());
+                    return Err(InterpreterError::OperatorError { line: variant.line, err_msg });
+                }
+
+                match value {
+                    TokenLiteral::LOX_ENUM_VALUE(value) if Rc::ptr_eq(&value.enum_type, &enum_type) && value.variant == index => {
+                        for (binding, item) in bindings.iter().zip(value.values.iter()) {
+                            if binding.lexeme != "_" {
+                                env.define(binding.lexeme.clone(), item.clone());
+                            }
+                        }
+                        Ok(true)
+                    }
+                    _ => Ok(false)
+                }
+            }
+        }
+    }
+
     fn visit_function_stmt(&mut self, stmt: &Stmt) -> Result<TokenLiteral, InterpreterError> {
         match stmt {
             Function { ptr } => {
@@ -236,7 +560,7 @@ impl Interpreter {
                 let function_obj = Function { ptr: Rc::clone(ptr) };
                 let function_obj = LoxFunction::new(function_obj, curr_env, false);
                 let function = Rc::new(LoxCallable::UserFunction(Rc::new(function_obj)));
-                self.curr_env.define(ptr.as_ref().name.lexeme.clone(), TokenLiteral::LOX_CALLABLE(function));
+                self.define_var(&ptr.name, TokenLiteral::LOX_CALLABLE(function), false)?;
                 Ok(TokenLiteral::LOX_NULL)
             }
             _ => unreachable!("Non-function statement passed to function visitor")
@@ -260,6 +584,126 @@ impl Interpreter {
         }
     }
 
+    fn visit_list_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
+        match expr {
+            List { elements, .. } => {
+                let mut values = Vec::with_capacity(elements.len());
+                for element in elements.iter() {
+                    values.push(self.accept_expr(element)?);
+                }
+                Ok(TokenLiteral::LOX_LIST(Rc::new(RefCell::new(values))))
+            }
+            _ => unreachable!("Non-list expression passed to list visitor")
+        }
+    }
+
+    fn visit_map_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
+        match expr {
+            Map { brace, entries } => {
+                let mut map = LoxMap::new();
+                for (key, value) in entries.iter() {
+                    let key = self.accept_expr(key)?;
+                    Interpreter::check_map_key(&key, brace)?;
+                    let value = self.accept_expr(value)?;
+                    map.insert(key, value);
+                }
+                Ok(TokenLiteral::LOX_MAP(Rc::new(RefCell::new(map))))
+            }
+            _ => unreachable!("Non-map expression passed to map visitor")
+        }
+    }
+
+    fn visit_index_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
+        match expr {
+            Index { object, bracket, index } => {
+                let object = self.accept_expr(object)?;
+                let index = self.accept_expr(index)?;
+                match object {
+                    TokenLiteral::LOX_LIST(list) => {
+                        let list = list.borrow();
+                        let i = Interpreter::list_index(list.len(), &index, bracket)?;
+                        Ok(list[i].clone())
+                    }
+                    // Missing keys read as nil
+                    TokenLiteral::LOX_MAP(map) => Ok(map.borrow().get(&index).unwrap_or(TokenLiteral::LOX_NULL)),
+                    TokenLiteral::LOX_STRING(string) => {
+                        let chars: Vec<char> = string.chars().collect();
+                        let i = Interpreter::list_index(chars.len(), &index, bracket)?;
+                        Ok(TokenLiteral::LOX_STRING(Rc::new(chars[i].to_string())))
+                    }
+                    _ => {
+                        let err_msg = String::from("Only lists, maps and strings can be indexed.");
+                        Err(InterpreterError::OperatorError { line: bracket.line, err_msg })
+                    }
+                }
+            }
+            _ => unreachable!("Non-index expression passed to index visitor")
+        }
+    }
+
+    fn visit_set_index_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
+        match expr {
+            SetIndex { object, bracket, index, value } => {
+                let object = self.accept_expr(object)?;
+                let index = self.accept_expr(index)?;
+                let value = self.accept_expr(value)?;
+                Interpreter::assign_index(object, index, value.clone(), bracket)?;
+                Ok(value)
+            }
+            _ => unreachable!("Non-set-index expression passed to set-index visitor")
+        }
+    }
+
+    fn assign_index(object: TokenLiteral, index: TokenLiteral, value: TokenLiteral, bracket: &Token) -> Result<(), InterpreterError> {
+        match object {
+            TokenLiteral::LOX_LIST(list) => {
+                let mut list = list.borrow_mut();
+                let i = Interpreter::list_index(list.len(), &index, bracket)?;
+                list[i] = value;
+                Ok(())
+            }
+            TokenLiteral::LOX_MAP(map) => {
+                Interpreter::check_map_key(&index, bracket)?;
+                map.borrow_mut().insert(index, value);
+                Ok(())
+            }
+            _ => {
+                let err_msg = String::from("Only lists and maps support index assignment.");
+                Err(InterpreterError::OperatorError { line: bracket.line, err_msg })
+            }
+        }
+    }
+
+    fn list_index(len: usize, index: &TokenLiteral, bracket: &Token) -> Result<usize, InterpreterError> {
+        match index {
+            TokenLiteral::LOX_INT(number) => {
+                if *number < 0 || *number as u64 >= len as u64 {
+                    let err_msg = format!("Index {number} out of range for length {len}.");
+                    return Err(InterpreterError::OperatorError { line: bracket.line, err_msg });
+                }
+                Ok(*number as usize)
+            }
+            TokenLiteral::LOX_BIGINT(number) => {
+                let err_msg = format!("Index {number} out of range for length {len}.");
+                Err(InterpreterError::OperatorError { line: bracket.line, err_msg })
+            }
+            _ => {
+                let err_msg = String::from("Index must be an integer.");
+                Err(InterpreterError::OperatorError { line: bracket.line, err_msg })
+            }
+        }
+    }
+
+    fn check_map_key(key: &TokenLiteral, token: &Token) -> Result<(), InterpreterError> {
+        match LoxMap::is_valid_key(key) {
+            true => Ok(()),
+            false => {
+                let err_msg = String::from("Map keys must be numbers, strings, booleans, nil or enum values.");
+                Err(InterpreterError::OperatorError { line: token.line, err_msg })
+            }
+        }
+    }
+
     fn visit_logical_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
         match expr {
             Logical { left, operator, right } => {
@@ -287,7 +731,41 @@ impl Interpreter {
                 // Recursively evaluate operands until they are usable literals
                 let left = self.accept_expr(left)?;
                 let right = self.accept_expr(right)?;
-                match (left, right) {
+                match Interpreter::promote(left, right) {
+                    // Two integers
+                    (TokenLiteral::LOX_INT(left), TokenLiteral::LOX_INT(right)) => {
+                        match operator.token_type {
+                            PLUS | MINUS | STAR | SLASH => Interpreter::int_arithmetic(left, right, operator),
+                            EQUAL_EQUAL => Ok(TokenLiteral::LOX_BOOL(left == right)),
+                            BANG_EQUAL => Ok(TokenLiteral::LOX_BOOL(left != right)),
+                            GREATER => Ok(TokenLiteral::LOX_BOOL(left > right)),
+                            GREATER_EQUAL => Ok(TokenLiteral::LOX_BOOL(left >= right)),
+                            LESS => Ok(TokenLiteral::LOX_BOOL(left < right)),
+                            LESS_EQUAL => Ok(TokenLiteral::LOX_BOOL(left <= right)),
+                            _ => {
+                                let err_msg = String::from("Unrecognized operator passed between two integers");
+                                Err(InterpreterError::OperatorError { line: operator.line, err_msg })
+                            }
+                        }
+                    }
+                    // Integers where at least one no longer fits in an i64
+                    (left @ (TokenLiteral::LOX_INT(_) | TokenLiteral::LOX_BIGINT(_)), right @ (TokenLiteral::LOX_INT(_) | TokenLiteral::LOX_BIGINT(_))) => {
+                        let left = BigInt::from_literal(&left).unwrap();
+                        let right = BigInt::from_literal(&right).unwrap();
+                        match operator.token_type {
+                            PLUS | MINUS | STAR | SLASH => Interpreter::big_arithmetic(&left, &right, operator),
+                            EQUAL_EQUAL => Ok(TokenLiteral::LOX_BOOL(left == right)),
+                            BANG_EQUAL => Ok(TokenLiteral::LOX_BOOL(left != right)),
+                            GREATER => Ok(TokenLiteral::LOX_BOOL(left > right)),
+                            GREATER_EQUAL => Ok(TokenLiteral::LOX_BOOL(left >= right)),
+                            LESS => Ok(TokenLiteral::LOX_BOOL(left < right)),
+                            LESS_EQUAL => Ok(TokenLiteral::LOX_BOOL(left <= right)),
+                            _ => {
+                                let err_msg = String::from("Unrecognized operator passed between two integers");
+                                Err(InterpreterError::OperatorError { line: operator.line, err_msg })
+                            }
+                        }
+                    }
                     // Two numbers
                     (TokenLiteral::LOX_NUMBER(left), TokenLiteral::LOX_NUMBER(right)) => {
                         match operator.token_type {
@@ -391,6 +869,47 @@ impl Interpreter {
                             }
                         }
                     }
+                    (TokenLiteral::LOX_LIST(left), TokenLiteral::LOX_LIST(right)) => {
+                        match operator.token_type {
+                            EQUAL_EQUAL => Ok(TokenLiteral::LOX_BOOL(Rc::ptr_eq(&left, &right))),
+                            BANG_EQUAL => Ok(TokenLiteral::LOX_BOOL(!Rc::ptr_eq(&left, &right))),
+                            _ => {
+                                let err_msg = String::from("Non-equality operators passed between two lists");
+                                Err(InterpreterError::OperatorError { line: operator.line, err_msg })
+                            }
+                        }
+                    }
+                    // Enums and their values
+                    (left @ (TokenLiteral::LOX_ENUM(_) | TokenLiteral::LOX_ENUM_VALUE(_)), right) => {
+                        match operator.token_type {
+                            EQUAL_EQUAL => Ok(TokenLiteral::LOX_BOOL(Interpreter::is_equal(left, right))),
+                            BANG_EQUAL => Ok(TokenLiteral::LOX_BOOL(!Interpreter::is_equal(left, right))),
+                            _ => {
+                                let err_msg = String::from("Non-equality operators passed between enum values");
+                                Err(InterpreterError::OperatorError { line: operator.line, err_msg })
+                            }
+                        }
+                    }
+                    (TokenLiteral::LOX_GENERATOR(left), TokenLiteral::LOX_GENERATOR(right)) => {
+                        match operator.token_type {
+                            EQUAL_EQUAL => Ok(TokenLiteral::LOX_BOOL(Rc::ptr_eq(&left, &right))),
+                            BANG_EQUAL => Ok(TokenLiteral::LOX_BOOL(!Rc::ptr_eq(&left, &right))),
+                            _ => {
+                                let err_msg = String::from("Non-equality operators passed between two generators");
+                                Err(InterpreterError::OperatorError { line: operator.line, err_msg })
+                            }
+                        }
+                    }
+                    (TokenLiteral::LOX_MAP(left), TokenLiteral::LOX_MAP(right)) => {
+                        match operator.token_type {
+                            EQUAL_EQUAL => Ok(TokenLiteral::LOX_BOOL(Rc::ptr_eq(&left, &right))),
+                            BANG_EQUAL => Ok(TokenLiteral::LOX_BOOL(!Rc::ptr_eq(&left, &right))),
+                            _ => {
+                                let err_msg = String::from("Non-equality operators passed between two maps");
+                                Err(InterpreterError::OperatorError { line: operator.line, err_msg })
+                            }
+                        }
+                    }
                     // Operands of arbitrary, non-equal types
                     (_, _) => match operator.token_type {
                         EQUAL_EQUAL => Ok(TokenLiteral::LOX_BOOL(false)),
@@ -406,6 +925,55 @@ impl Interpreter {
         }
     }
 
+    /// An integer operated on together with a float is converted to a float first
+    fn promote(left: TokenLiteral, right: TokenLiteral) -> (TokenLiteral, TokenLiteral) {
+        match (left, right) {
+            (TokenLiteral::LOX_INT(left), right @ TokenLiteral::LOX_NUMBER(_)) => (TokenLiteral::LOX_NUMBER(left as f64), right),
+            (left @ TokenLiteral::LOX_NUMBER(_), TokenLiteral::LOX_INT(right)) => (left, TokenLiteral::LOX_NUMBER(right as f64)),
+            (TokenLiteral::LOX_BIGINT(left), right @ TokenLiteral::LOX_NUMBER(_)) => (TokenLiteral::LOX_NUMBER(left.to_f64()), right),
+            (left @ TokenLiteral::LOX_NUMBER(_), TokenLiteral::LOX_BIGINT(right)) => (left, TokenLiteral::LOX_NUMBER(right.to_f64())),
+            (left, right) => (left, right),
+        }
+    }
+
+    /// Integer arithmetic never silently wraps: results that overflow an i64 become big integers
+    /// Division truncates towards zero
+    fn int_arithmetic(left: i64, right: i64, operator: &Token) -> Result<TokenLiteral, InterpreterError> {
+        let result = match operator.token_type {
+            PLUS => left.checked_add(right),
+            MINUS => left.checked_sub(right),
+            STAR => left.checked_mul(right),
+            SLASH if right == 0 => {
+                let err_msg = String::from("Integer division by zero.");
+                return Err(InterpreterError::OperatorError { line: operator.line, err_msg });
+            }
+            SLASH => left.checked_div(right),
+            _ => unreachable!("Only arithmetic operators are passed to integer arithmetic")
+        };
+        match result {
+            Some(result) => Ok(TokenLiteral::LOX_INT(result)),
+            None => Interpreter::big_arithmetic(&BigInt::from_i64(left), &BigInt::from_i64(right), operator),
+        }
+    }
+
+    fn big_arithmetic(left: &BigInt, right: &BigInt, operator: &Token) -> Result<TokenLiteral, InterpreterError> {
+        let result = match operator.token_type {
+            PLUS => left.add(right),
+            MINUS => left.sub(right),
+            STAR => left.mul(right),
+            SLASH => match left.divmod(right) {
+                Some((quotient, _)) => quotient,
+                None => {
+                    let err_msg = String::from("Integer division by zero.");
+                    return Err(InterpreterError::OperatorError { line: operator.line, err_msg });
+                }
+            }
+            _ => unreachable!("Only arithmetic operators are passed to integer arithmetic")
+        };
+        Ok(result.into_literal())
+    }
+
+
     fn visit_call_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
         match expr {
             Call { callee, paren, arguments } => {
@@ -416,21 +984,7 @@ impl Interpreter {
                 }
 
                 match callee {
-                    TokenLiteral::LOX_CALLABLE(callable) => {
-                        match callable.arity() == parameters.len() {
-                            true => {
-                                if let LoxCallable::ClassConstructor(_) = *callable {
-                                    // Add class instance as last parameter
-                                    parameters.push(TokenLiteral::LOX_CALLABLE(Rc::clone(&callable)));
-                                }
-                                callable.call(self, parameters)
-                            },
-                            false => {
-                                let err_msg = format!("Expected {} arguments but got {}.", callable.arity(), parameters.len());
-                                Err(InterpreterError::OperatorError { line: paren.line, err_msg})
-                            }
-                        }
-                    }
+                    TokenLiteral::LOX_CALLABLE(callable) => self.call_callable(callable, parameters, paren.line),
                     _ => {
                         let err_msg = String::from("Can only call functions and class instances");
                         Err(InterpreterError::OperatorError { line: paren.line, err_msg})
@@ -441,6 +995,25 @@ impl Interpreter {
         }
     }
 
+    pub fn call_callable(&mut self, callable: Rc<LoxCallable>, mut parameters: Vec<TokenLiteral>, line: i32) -> Result<TokenLiteral, InterpreterError> {
+        match callable.arity().accepts(parameters.len()) {
+            true => {
+                if let LoxCallable::ClassConstructor(_) = *callable {
+                    // Add class instance as last parameter
+                    parameters.push(TokenLiteral::LOX_CALLABLE(Rc::clone(&callable)));
+                }
+                callable.call(self, parameters).map_err(|error| match error {
+                    InterpreterError::NativeError(err_msg) => InterpreterError::OperatorError { line, err_msg },
+                    error => error,
+                })
+            },
+            false => {
+                let err_msg = format!("Expected {} arguments but got {}.", callable.arity(), parameters.len());
+                Err(InterpreterError::OperatorError { line, err_msg})
+            }
+        }
+    }
+
     fn visit_unary_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
         match expr {
             Unary { operator, right } => {
@@ -448,6 +1021,11 @@ impl Interpreter {
                 match operator.token_type {
                     MINUS => match right {
                         TokenLiteral::LOX_NUMBER(num) => Ok(TokenLiteral::LOX_NUMBER(-num)),
+                        TokenLiteral::LOX_INT(num) => match num.checked_neg() {
+                            Some(num) => Ok(TokenLiteral::LOX_INT(num)),
+                            None => Ok(BigInt::from_i64(num).neg().into_literal()),
+                        },
+                        TokenLiteral::LOX_BIGINT(num) => Ok(num.neg().into_literal()),
                         _ => {
                             let err_msg = String::from("Minus operator used on non-numerical operand");
                             Err(InterpreterError::OperatorError { line: operator.line, err_msg })
@@ -497,12 +1075,72 @@ impl Interpreter {
         }
     }
 
+    fn visit_unpack_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
+        match expr {
+            Unpack { bracket, targets, value } => {
+                // The whole right-hand side is evaluated before any target is written, so swaps work
+                let value = self.accept_expr(value)?;
+                let values = Interpreter::unpack_list(bracket, &value, targets.len())?;
+                for (target, element) in zip(targets.iter(), values) {
+                    match target {
+                        Variable { name, id } => match self.locals.get(id) {
+                            Some(distance) => self.curr_env.deref().assign_at(*distance, name, element),
+                            None => self.global_env.deref().assign(name, element),
+                        }?,
+                        Get { object, name, .. } => match self.accept_expr(object)? {
+                            TokenLiteral::LOX_INSTANCE(instance) => instance.set(name, element),
+                            _ => {
+                                let err_msg = String::from("Only instances have fields.");
+                                return Err(InterpreterError::OperatorError { err_msg, line: name.line });
+                            }
+                        },
+                        Index { object, bracket, index } => {
+                            let object = self.accept_expr(object)?;
+                            let index = self.accept_expr(index)?;
+                            Interpreter::assign_index(object, index, element, bracket)?;
+                        }
+                        _ => unreachable!("Parser only allows variables and properties as unpack targets")
+                    }
+                }
+                Ok(value)
+            }
+            _ => unreachable!("Non-unpack expression passed to unpack visitor")
+        }
+    }
+
+    fn unpack_list(bracket: &Token, value: &TokenLiteral, count: usize) -> Result<Vec<TokenLiteral>, InterpreterError> {
+        match value {
+            TokenLiteral::LOX_LIST(list) => {
+                let list = list.borrow();
+                if list.len() != count {
+                    let err_msg = format!("Expected {count} values to unpack but got {}.", list.len());
+                    return Err(InterpreterError::OperatorError { line: bracket.line, err_msg });
+                }
+                Ok(list.clone())
+            }
+            _ => {
+                let err_msg = String::from("Only lists can be destructured by position.");
+                Err(InterpreterError::OperatorError { line: bracket.line, err_msg })
+            }
+        }
+    }
+
     fn visit_get_expr(&mut self, expr: &Expr) -> Result<TokenLiteral, InterpreterError> {
         match expr {
             Get { object, name , .. } => {
                 let object = self.accept_expr(object)?;
                 match object {
                     TokenLiteral::LOX_INSTANCE(instance) => instance.get(Rc::clone(&instance), name),
+                    TokenLiteral::LOX_ENUM(enum_type) => LoxEnum::get(&enum_type, name),
+                    TokenLiteral::LOX_ENUM_VALUE(value) => value.get(name),
+                    TokenLiteral::LOX_GENERATOR(generator) if name.lexeme == "next" => {
+                        let next = LoxCallable::Native(NativeFunction::GeneratorNext(generator));
+                        Ok(TokenLiteral::LOX_CALLABLE(Rc::new(next)))
+                    }
+                    TokenLiteral::LOX_GENERATOR(generator) if name.lexeme == "done" => {
+                        let done = LoxCallable::Native(NativeFunction::GeneratorDone(generator));
+                        Ok(TokenLiteral::LOX_CALLABLE(Rc::new(done)))
+                    }
                     _ => {
                         let err_msg = String::from("Only instances have properties.");
                         Err(InterpreterError::OperatorError { err_msg, line: name.line})
@@ -545,7 +1183,7 @@ impl Interpreter {
             unreachable!("'super' maps to Lox_Callable token literals")
         };
 
-        let dummy_this = Token { token_type: NIL, line: -1, lexeme: String::from("this"), literal: TokenLiteral::LOX_NULL};
+        let dummy_this = Token { token_type: NIL, line: -1, lexeme: String::from("this"), literal: TokenLiteral::LOX_NULL, column: 0};
         let TokenLiteral::LOX_INSTANCE(instance) = self.curr_env.get_at(*distance - 1, &dummy_this)? else {
             unreachable!()
         };
@@ -562,7 +1200,7 @@ impl Interpreter {
         self.lookup_variable(expr)
     }
 
-    fn is_truthy(literal: &TokenLiteral) -> bool {
+    pub fn is_truthy(literal: &TokenLiteral) -> bool {
         match literal {
             TokenLiteral::LOX_BOOL(bool_value) => *bool_value,
             TokenLiteral::LOX_NULL => false,
@@ -570,12 +1208,27 @@ impl Interpreter {
         }
     }
 
-    fn is_equal(left: TokenLiteral, right: TokenLiteral) -> bool {
+    pub fn is_equal(left: TokenLiteral, right: TokenLiteral) -> bool {
         match (left, right) {
             (TokenLiteral::LOX_NUMBER(left), TokenLiteral::LOX_NUMBER(right)) => left == right,
+            (TokenLiteral::LOX_INT(left), TokenLiteral::LOX_INT(right)) => left == right,
+            (TokenLiteral::LOX_INT(left), TokenLiteral::LOX_NUMBER(right)) => left as f64 == right,
+            (TokenLiteral::LOX_NUMBER(left), TokenLiteral::LOX_INT(right)) => left == right as f64,
+            // Big integers are never within i64 range, so they can only equal other big integers and floats
+            (TokenLiteral::LOX_BIGINT(left), TokenLiteral::LOX_BIGINT(right)) => left == right,
+            (TokenLiteral::LOX_BIGINT(left), TokenLiteral::LOX_NUMBER(right)) => left.to_f64() == right,
+            (TokenLiteral::LOX_NUMBER(left), TokenLiteral::LOX_BIGINT(right)) => left == right.to_f64(),
             (TokenLiteral::LOX_STRING(left), TokenLiteral::LOX_STRING(right)) => left == right,
             (TokenLiteral::LOX_BOOL(left), TokenLiteral::LOX_BOOL(right)) => left == right,
             (TokenLiteral::LOX_NULL, TokenLiteral::LOX_NULL) => true,
+            (TokenLiteral::LOX_ENUM_VALUE(left), TokenLiteral::LOX_ENUM_VALUE(right)) => left.equals(&right),
+            // Everything else is compared by identity
+            (TokenLiteral::LOX_CALLABLE(left), TokenLiteral::LOX_CALLABLE(right)) => Rc::ptr_eq(&left, &right),
+            (TokenLiteral::LOX_INSTANCE(left), TokenLiteral::LOX_INSTANCE(right)) => Rc::ptr_eq(&left, &right),
+            (TokenLiteral::LOX_LIST(left), TokenLiteral::LOX_LIST(right)) => Rc::ptr_eq(&left, &right),
+            (TokenLiteral::LOX_MAP(left), TokenLiteral::LOX_MAP(right)) => Rc::ptr_eq(&left, &right),
+            (TokenLiteral::LOX_GENERATOR(left), TokenLiteral::LOX_GENERATOR(right)) => Rc::ptr_eq(&left, &right),
+            (TokenLiteral::LOX_ENUM(left), TokenLiteral::LOX_ENUM(right)) => Rc::ptr_eq(&left, &right),
             (_, _) => false,
         }
     }
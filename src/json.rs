@@ -0,0 +1,442 @@
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter, Write};
+use std::iter::Peekable;
+use std::rc::Rc;
+use std::str::Chars;
+
+use crate::bigint::BigInt;
+use crate::interpreter::InterpreterError;
+use crate::map::LoxMap;
+use crate::token_literal::TokenLiteral::{self, LOX_BIGINT, LOX_BOOL, LOX_INT, LOX_LIST, LOX_MAP, LOX_NULL, LOX_NUMBER, LOX_STRING};
+
+// Converts between JSON text and Lox values
+// Objects become maps with string keys, arrays become lists, and numbers without a fraction or exponent stay integers
+#[derive(Clone, Copy)]
+pub enum JsonNative {
+    Parse,
+    Stringify,
+}
+
+impl JsonNative {
+    pub const ALL: [JsonNative; 2] = [JsonNative::Parse, JsonNative::Stringify];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JsonNative::Parse => "jsonParse",
+            JsonNative::Stringify => "jsonStringify",
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            JsonNative::Parse => 1,
+            JsonNative::Stringify => 2,
+        }
+    }
+
+    pub fn call(&self, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match self {
+            JsonNative::Parse => match &arguments[0] {
+                LOX_STRING(text) => parse(text).map_err(|err| self.error(format!("{err}."))),
+                other => Err(self.error(format!("expects a string but got {other}."))),
+            },
+            // An indent of nil or 0 gives compact output
+            JsonNative::Stringify => {
+                let indent = match &arguments[1] {
+                    LOX_NULL => 0,
+                    LOX_INT(indent) if *indent >= 0 => *indent as usize,
+                    other => return Err(self.error(format!("expects nil or a non-negative integer indent but got {other}."))),
+                };
+                let json = stringify(&arguments[0], indent).map_err(|err| self.error(format!("{err}.")))?;
+                Ok(LOX_STRING(Rc::new(json)))
+            }
+        }
+    }
+
+    fn error(&self, message: String) -> InterpreterError {
+        InterpreterError::NativeError(format!("{}() {message}", self.name()))
+    }
+}
+
+// Deepest nesting of arrays and objects `parse` accepts, as each level recurses on the native stack
+const MAX_DEPTH: usize = 256;
+
+pub struct JsonError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at line {}, column {}", self.message, self.line, self.column)
+    }
+}
+
+/// Parses a complete JSON document; anything but whitespace after the value is an error
+pub fn parse(text: &str) -> Result<TokenLiteral, JsonError> {
+    let mut parser = JsonParser { chars: text.chars().peekable(), line: 1, column: 1, depth: 0 };
+    parser.skip_whitespace();
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    match parser.chars.peek().copied() {
+        None => Ok(value),
+        Some(c) => Err(parser.error(format!("unexpected '{c}' after the value"))),
+    }
+}
+
+/// Encodes a value, putting each element on its own line when `indent` is positive
+pub fn stringify(value: &TokenLiteral, indent: usize) -> Result<String, String> {
+    let mut writer = JsonWriter { out: String::new(), indent, open: Vec::new() };
+    writer.value(value, 0)?;
+    Ok(writer.out)
+}
+
+struct JsonParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+    // Arrays and objects currently open around the parser's position
+    depth: usize,
+}
+
+impl JsonParser<'_> {
+    fn value(&mut self) -> Result<TokenLiteral, JsonError> {
+        match self.chars.peek().copied() {
+            Some('{') => self.nested(Self::object),
+            Some('[') => self.nested(Self::array),
+            Some('"') => Ok(LOX_STRING(Rc::new(self.string()?))),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.keyword("true", LOX_BOOL(true)),
+            Some('f') => self.keyword("false", LOX_BOOL(false)),
+            Some('n') => self.keyword("null", LOX_NULL),
+            Some(c) => Err(self.error(format!("unexpected '{c}'"))),
+            None => Err(self.error(String::from("unexpected end of input"))),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<TokenLiteral, JsonError>) -> Result<TokenLiteral, JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(format!("nesting deeper than {MAX_DEPTH} levels")));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<TokenLiteral, JsonError> {
+        self.advance();
+        let mut map = LoxMap::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.advance();
+            return Ok(LOX_MAP(Rc::new(RefCell::new(map))));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() != Some(&'"') {
+                return Err(self.error(String::from("expected a string key")));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            self.skip_whitespace();
+            let value = self.value()?;
+            map.insert(LOX_STRING(Rc::new(key)), value);
+            self.skip_whitespace();
+            match self.advance() {
+                Some(',') => continue,
+                None => return Err(self.error(String::from("unexpected end of input"))),
+                Some('}') => return Ok(LOX_MAP(Rc::new(RefCell::new(map)))),
+                _ => return Err(self.error_before(String::from("expected ',' or '}' in object"))),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<TokenLiteral, JsonError> {
+        self.advance();
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.advance();
+            return Ok(LOX_LIST(Rc::new(RefCell::new(items))));
+        }
+
+        loop {
+            self.skip_whitespace();
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.advance() {
+                Some(',') => continue,
+                None => return Err(self.error(String::from("unexpected end of input"))),
+                Some(']') => return Ok(LOX_LIST(Rc::new(RefCell::new(items)))),
+                _ => return Err(self.error_before(String::from("expected ',' or ']' in array"))),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.advance();
+        let mut string = String::new();
+        loop {
+            match self.advance() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.escape()?),
+                Some(c) if (c as u32) < 0x20 => return Err(self.error_before(String::from("unescaped control character in string"))),
+                Some(c) => string.push(c),
+                None => return Err(self.error(String::from("unterminated string"))),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, JsonError> {
+        match self.advance() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('/') => Ok('/'),
+            Some('b') => Ok('\u{8}'),
+            Some('f') => Ok('\u{c}'),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('u') => {
+                let high = self.hex_code()?;
+                // Characters outside the basic plane are written as a pair of surrogate escapes
+                if (0xD800..0xDC00).contains(&high) {
+                    if self.advance() != Some('\\') || self.advance() != Some('u') {
+                        return Err(self.error_before(String::from("expected a low surrogate escape")));
+                    }
+                    let low = self.hex_code()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error_before(String::from("invalid low surrogate")));
+                    }
+                    let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                    return Ok(char::from_u32(code).unwrap());
+                }
+                char::from_u32(high).ok_or_else(|| self.error_before(String::from("unpaired surrogate escape")))
+            }
+            Some(c) => Err(self.error_before(format!("invalid escape '\\{c}'"))),
+            None => Err(self.error(String::from("unterminated string"))),
+        }
+    }
+
+    fn hex_code(&mut self) -> Result<u32, JsonError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.advance().and_then(|c| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(self.error_before(String::from("expected four hex digits in '\\u' escape"))),
+            }
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<TokenLiteral, JsonError> {
+        let (line, column) = (self.line, self.column);
+        let mut text = String::new();
+        let mut is_float = false;
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '0'..='9' | '-' | '+' => (),
+                '.' | 'e' | 'E' => is_float = true,
+                _ => break,
+            }
+            text.push(c);
+            self.advance();
+        }
+
+        if !JsonParser::is_valid_number(&text) {
+            return Err(JsonError { line, column, message: format!("invalid number '{text}'") });
+        }
+        if is_float {
+            return Ok(LOX_NUMBER(text.parse().unwrap()));
+        }
+        match text.parse::<i64>() {
+            Ok(int) => Ok(LOX_INT(int)),
+            Err(_) => Ok(LOX_BIGINT(Rc::new(BigInt::parse(&text).unwrap()))),
+        }
+    }
+
+    // -?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?
+    fn is_valid_number(text: &str) -> bool {
+        let text = text.strip_prefix('-').unwrap_or(text);
+        let int_end = text.find(['.', 'e', 'E']).unwrap_or(text.len());
+        let (int, mut rest) = text.split_at(int_end);
+        let digits = |s: &str| !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit());
+        if !digits(int) || (int.len() > 1 && int.starts_with('0')) {
+            return false;
+        }
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let fraction_end = fraction.find(['e', 'E']).unwrap_or(fraction.len());
+            if !digits(&fraction[..fraction_end]) {
+                return false;
+            }
+            rest = &fraction[fraction_end..];
+        }
+        match rest.strip_prefix(['e', 'E']) {
+            Some(exponent) => digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent)),
+            None => rest.is_empty(),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: TokenLiteral) -> Result<TokenLiteral, JsonError> {
+        let (line, column) = (self.line, self.column);
+        for expected in word.chars() {
+            if self.chars.peek() != Some(&expected) {
+                return Err(JsonError { line, column, message: format!("expected '{word}'") });
+            }
+            self.advance();
+        }
+        Ok(value)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        match self.chars.peek() {
+            Some(&c) if c == expected => {
+                self.advance();
+                Ok(())
+            }
+            _ => Err(self.error(format!("expected '{expected}'"))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.chars.peek() {
+            self.advance();
+        }
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    // At the character about to be read
+    fn error(&self, message: String) -> JsonError {
+        JsonError { line: self.line, column: self.column, message }
+    }
+
+    // At the character just read
+    fn error_before(&self, message: String) -> JsonError {
+        JsonError { line: self.line, column: self.column.saturating_sub(1).max(1), message }
+    }
+}
+
+struct JsonWriter {
+    out: String,
+    indent: usize,
+    // Lists and maps currently being written, to reject values that contain themselves
+    open: Vec<*const ()>,
+}
+
+impl JsonWriter {
+    fn value(&mut self, value: &TokenLiteral, depth: usize) -> Result<(), String> {
+        match value {
+            LOX_NULL => self.out.push_str("null"),
+            LOX_BOOL(b) => write!(self.out, "{b}").unwrap(),
+            LOX_INT(_) | LOX_BIGINT(_) => write!(self.out, "{value}").unwrap(),
+            LOX_NUMBER(number) if number.is_finite() => write!(self.out, "{value}").unwrap(),
+            LOX_NUMBER(number) => return Err(format!("cannot encode {number}")),
+            LOX_STRING(string) => self.string(string),
+            LOX_LIST(list) => {
+                self.enter(Rc::as_ptr(list) as *const ())?;
+                let items = list.borrow();
+                self.out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    self.separator(i, depth + 1);
+                    self.value(item, depth + 1)?;
+                }
+                self.close(!items.is_empty(), depth, ']');
+            }
+            LOX_MAP(map) => {
+                self.enter(Rc::as_ptr(map) as *const ())?;
+                let map = map.borrow();
+                self.out.push('{');
+                for (i, (key, value)) in map.entries().enumerate() {
+                    let LOX_STRING(key) = key else {
+                        return Err(format!("cannot encode map key {key}; keys must be strings"));
+                    };
+                    self.separator(i, depth + 1);
+                    self.field(key, value, depth)?;
+                }
+                self.close(map.len() > 0, depth, '}');
+            }
+            // Plain instances are written as objects of their fields
+            TokenLiteral::LOX_INSTANCE(instance) => {
+                self.enter(Rc::as_ptr(instance) as *const ())?;
+                let fields = instance.fields();
+                self.out.push('{');
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    self.separator(i, depth + 1);
+                    self.field(name, value, depth)?;
+                }
+                self.close(!fields.is_empty(), depth, '}');
+            }
+            other => return Err(format!("cannot encode {other}")),
+        }
+        Ok(())
+    }
+
+    fn field(&mut self, key: &str, value: &TokenLiteral, depth: usize) -> Result<(), String> {
+        self.string(key);
+        self.out.push(':');
+        if self.indent > 0 {
+            self.out.push(' ');
+        }
+        self.value(value, depth + 1)
+    }
+
+    fn string(&mut self, string: &str) {
+        self.out.push('"');
+        for c in string.chars() {
+            match c {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                c if (c as u32) < 0x20 => write!(self.out, "\\u{:04x}", c as u32).unwrap(),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+    }
+
+    fn enter(&mut self, pointer: *const ()) -> Result<(), String> {
+        if self.open.contains(&pointer) {
+            return Err(String::from("cannot encode a value that contains itself"));
+        }
+        self.open.push(pointer);
+        Ok(())
+    }
+
+    fn separator(&mut self, index: usize, depth: usize) {
+        if index > 0 {
+            self.out.push(',');
+        }
+        self.newline(depth);
+    }
+
+    fn close(&mut self, had_items: bool, depth: usize, bracket: char) {
+        self.open.pop();
+        if had_items {
+            self.newline(depth);
+        }
+        self.out.push(bracket);
+    }
+
+    fn newline(&mut self, depth: usize) {
+        if self.indent > 0 {
+            self.out.push('\n');
+            self.out.push_str(&" ".repeat(self.indent * depth));
+        }
+    }
+}
@@ -0,0 +1,47 @@
+// The interpreter as a library, so that host programs can embed it and register their own natives
+// The `rlox` binary in main.rs is a thin command line on top of it
+
+mod token_type;
+mod token;
+mod scanner;
+mod token_literal;
+pub mod lox;
+mod parser;
+mod expression;
+mod interpreter;
+mod statement;
+mod environment;
+mod callable;
+mod function;
+mod clock;
+mod conversion;
+mod math;
+mod random;
+mod file_io;
+mod process;
+mod json;
+pub mod testing;
+mod strings;
+mod function_object;
+mod resolver;
+mod class;
+mod class_instance;
+mod native_function;
+mod native_class;
+mod pattern;
+mod map;
+mod generator;
+mod enum_type;
+mod bigint;
+mod ast_printer;
+pub mod formatter;
+pub mod linter;
+pub mod lsp;
+
+pub use callable::Arity;
+pub use class::LoxClass;
+pub use class_instance::LoxInstance;
+pub use environment::Environment;
+pub use interpreter::{Interpreter, InterpreterError};
+pub use native_class::{NativeConstructor, NativeMethod};
+pub use token_literal::TokenLiteral;
@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+use std::fs;
+
+use crate::callable::Arity;
+use crate::environment::Environment;
+use crate::expression::Expr;
+use crate::interpreter::Interpreter;
+use crate::lox;
+use crate::parser::Parser;
+use crate::pattern::Pattern;
+use crate::resolver::{Binding, BindingKind, Resolver};
+use crate::scanner::Scanner;
+use crate::statement::Stmt;
+use crate::token::Token;
+use crate::token_type::TokenType::*;
+
+// A comment starting with this turns lints off for the whole file, e.g. `// lint-disable: unused-variable, empty-block`
+const DISABLE_DIRECTIVE: &str = "lint-disable:";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LintCode {
+    UnusedVariable,
+    UnusedParameter,
+    UnreachableCode,
+    ShadowedVariable,
+    AssignmentInCondition,
+    EmptyBlock,
+    WrongArity,
+}
+
+impl LintCode {
+    pub const ALL: [LintCode; 7] = [
+        LintCode::UnusedVariable,
+        LintCode::UnusedParameter,
+        LintCode::UnreachableCode,
+        LintCode::ShadowedVariable,
+        LintCode::AssignmentInCondition,
+        LintCode::EmptyBlock,
+        LintCode::WrongArity,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintCode::UnusedVariable => "unused-variable",
+            LintCode::UnusedParameter => "unused-parameter",
+            LintCode::UnreachableCode => "unreachable-code",
+            LintCode::ShadowedVariable => "shadowed-variable",
+            LintCode::AssignmentInCondition => "assignment-in-condition",
+            LintCode::EmptyBlock => "empty-block",
+            LintCode::WrongArity => "wrong-arity",
+        }
+    }
+}
+
+pub struct Lint {
+    pub code: LintCode,
+    pub line: i32,
+    pub column: i32,
+    // Number of characters flagged, starting at the column
+    pub length: usize,
+    pub message: String,
+}
+
+/// Lints the given files, printing one warning per line; returns the process exit code
+pub fn run_lint(paths: &[String]) -> i32 {
+    let (mut warnings, mut broken) = (0, 0);
+    for file in lox::lox_files(paths) {
+        let source = match fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("{}: {err}", file.display());
+                broken += 1;
+                continue;
+            }
+        };
+        let Some(lints) = lint_source(&source) else {
+            eprintln!("{}: not linted because of syntax errors", file.display());
+            broken += 1;
+            continue;
+        };
+        for lint in &lints {
+            println!("{}:{}:{}: warning[{}]: {}", file.display(), lint.line, lint.column, lint.code.name(), lint.message);
+        }
+        warnings += lints.len();
+    }
+
+    match (broken, warnings) {
+        (0, 0) => 0,
+        (0, _) => 1,
+        _ => 65,
+    }
+}
+
+/// The lints of a whole file in source order, leaving out the ones the file disables, or None if it doesn't parse
+pub fn lint_source(source: &str) -> Option<Vec<Lint>> {
+    lox::clear_error();
+    let statements = Parser::new(Scanner::new(source.to_string()).scan_tokens()).parse();
+    let statements = match statements {
+        Ok(statements) if !lox::had_error() => statements,
+        _ => return None,
+    };
+
+    // Scope errors make the file as broken as syntax errors do
+    let mut interpreter = Interpreter::new();
+    let mut resolver = Resolver::new(&mut interpreter);
+    resolver.resolve_statements(&statements);
+    let bindings = resolver.bindings();
+    if lox::had_error() {
+        return None;
+    }
+
+    let tokens = Scanner::with_comments(source.to_string()).scan_tokens();
+    let mut linter = Linter::new(&statements, &interpreter.locals, &tokens);
+    linter.bindings(bindings);
+    linter.statements(&statements);
+    linter.empty_blocks(&tokens);
+
+    let disabled = disabled_codes(&tokens);
+    let mut lints: Vec<Lint> = linter.lints.into_iter().filter(|lint| !disabled.contains(&lint.code)).collect();
+    lints.sort_by_key(|lint| (lint.line, lint.column));
+    Some(lints)
+}
+
+fn disabled_codes(tokens: &[Token]) -> Vec<LintCode> {
+    let mut disabled = Vec::new();
+    for comment in tokens.iter().filter(|token| token.token_type == COMMENT) {
+        let text = comment.lexeme.trim_start_matches('/').trim();
+        if let Some(names) = text.strip_prefix(DISABLE_DIRECTIVE) {
+            for name in names.split([',', ' ']).filter(|name| !name.is_empty()) {
+                disabled.extend(LintCode::ALL.into_iter().find(|code| code.name() == name));
+            }
+        }
+    }
+    disabled
+}
+
+struct Linter<'a> {
+    // Arity of the natives and of the functions and classes declared at the top level
+    known_arities: HashMap<String, Arity>,
+    // Ids of the variable expressions the resolver found to be local, which never refer to those globals
+    locals: &'a HashMap<usize, usize>,
+    // Every token of the file, comments included, for positions the tree doesn't keep
+    tokens: &'a [Token],
+    lints: Vec<Lint>,
+}
+
+impl<'a> Linter<'a> {
+    fn new(statements: &[Stmt], locals: &'a HashMap<usize, usize>, tokens: &'a [Token]) -> Self {
+        Self { known_arities: Linter::global_arities(statements), locals, tokens, lints: Vec::new() }
+    }
+
+    fn global_arities(statements: &[Stmt]) -> HashMap<String, Arity> {
+        let mut arities = Environment::native_arities();
+
+        for statement in statements {
+            match statement {
+                Stmt::Function { ptr } => {
+                    arities.insert(ptr.name.lexeme.clone(), Arity::Exact(ptr.params.len()));
+                }
+                Stmt::Class { name, methods, .. } => {
+                    let init = methods.iter().find_map(|method| match method {
+                        Stmt::Function { ptr } if ptr.name.lexeme == "init" => Some(ptr.params.len()),
+                        _ => None,
+                    });
+                    // Arguments go to the superclass initializer when there is no `init`, which can't be checked here
+                    match (init, statement) {
+                        (Some(arity), _) => arities.insert(name.lexeme.clone(), Arity::Exact(arity)),
+                        (None, Stmt::Class { superclass: None, .. }) => arities.insert(name.lexeme.clone(), Arity::Exact(0)),
+                        _ => arities.remove(&name.lexeme),
+                    };
+                }
+                // Anything could be assigned to a variable
+                Stmt::Var { target, .. } => {
+                    for name in target.names() {
+                        arities.remove(&name.lexeme);
+                    }
+                }
+                _ => (),
+            }
+        }
+        arities
+    }
+
+    fn lint(&mut self, code: LintCode, token: &Token, message: String) {
+        self.lints.push(Lint { code, line: token.line, column: token.column, length: token.lexeme.chars().count(), message });
+    }
+
+    // Names starting with `_` are unused on purpose
+    fn bindings(&mut self, bindings: Vec<Binding>) {
+        for binding in bindings {
+            let length = binding.name.chars().count();
+            if binding.shadows {
+                let message = format!("'{}' shadows a variable from an enclosing scope.", binding.name);
+                self.lints.push(Lint { code: LintCode::ShadowedVariable, line: binding.line, column: binding.column, length, message });
+            }
+            if binding.read || binding.name.starts_with('_') {
+                continue;
+            }
+            let (code, what) = match binding.kind {
+                BindingKind::Method => continue,
+                BindingKind::Variable => (LintCode::UnusedVariable, "Local variable"),
+                BindingKind::Function => (LintCode::UnusedVariable, "Local function"),
+                BindingKind::Parameter => (LintCode::UnusedParameter, "Parameter"),
+            };
+            let message = format!("{what} '{}' is never used.", binding.name);
+            self.lints.push(Lint { code, line: binding.line, column: binding.column, length, message });
+        }
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) {
+        for (i, statement) in statements.iter().enumerate() {
+            self.stmt(statement);
+            let jump = match statement {
+                Stmt::Return { keyword, .. } | Stmt::Break { keyword } | Stmt::Continue { keyword } => Some(keyword),
+                _ => None,
+            };
+            if let Some(keyword) = jump.filter(|_| i + 1 < statements.len()) {
+                // Reported on the first dead statement, which is whatever follows the jump's semicolon
+                if let Some(dead) = self.token_after_statement(keyword) {
+                    self.lint(LintCode::UnreachableCode, dead, format!("Code after '{}' is unreachable.", keyword.lexeme));
+                }
+            }
+        }
+    }
+
+    // The first token after the `;` ending the statement that starts with `first`
+    fn token_after_statement(&self, first: &Token) -> Option<&'a Token> {
+        let start = self.tokens.binary_search_by_key(&(first.line, first.column), |token| (token.line, token.column)).ok()?;
+        let mut depth = 0;
+        let mut rest = self.tokens[start..].iter().filter(|token| token.token_type != COMMENT);
+        for token in rest.by_ref() {
+            match token.token_type {
+                LEFT_PAREN | LEFT_BRACE | LEFT_BRACKET => depth += 1,
+                RIGHT_PAREN | RIGHT_BRACE | RIGHT_BRACKET => depth -= 1,
+                SEMICOLON if depth == 0 => break,
+                _ => (),
+            }
+        }
+        rest.next()
+    }
+
+    // Scopes are left to the resolver; this only walks the tree for the lints that don't depend on them
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Block { statements } => self.statements(statements),
+            Stmt::Break { .. } | Stmt::Continue { .. } | Stmt::Enum { .. } => (),
+            Stmt::Class { superclass, methods, .. } => {
+                if let Some(superclass) = superclass {
+                    self.expr(superclass);
+                }
+                for method in methods {
+                    self.stmt(method);
+                }
+            }
+            Stmt::Expression { expression } | Stmt::Print { expression } => self.expr(expression),
+            Stmt::ForIn { iterable, body, .. } => {
+                self.expr(iterable);
+                self.stmt(body);
+            }
+            Stmt::Function { ptr } => self.statements(&ptr.body),
+            Stmt::If { expression, then_branch, else_branch } => {
+                self.condition(expression);
+                self.stmt(then_branch);
+                self.stmt(else_branch);
+            }
+            Stmt::Match { subject, arms, .. } => {
+                self.expr(subject);
+                for arm in arms {
+                    match &arm.pattern {
+                        Pattern::Class { class, .. } => self.expr(class),
+                        Pattern::Enum { enum_type, .. } => self.expr(enum_type),
+                        _ => (),
+                    }
+                    if let Some(guard) = &arm.guard {
+                        self.expr(guard);
+                    }
+                    self.stmt(&arm.body);
+                }
+            }
+            Stmt::Return { value, .. } | Stmt::Yield { value, .. } => self.expr(value),
+            Stmt::Try { body, handler, .. } => {
+                self.stmt(body);
+                self.stmt(handler);
+            }
+            Stmt::Var { initializer, .. } => self.expr(initializer),
+            Stmt::While { expression, body, increment } => {
+                self.condition(expression);
+                self.stmt(body);
+                if let Some(increment) = increment {
+                    self.expr(increment);
+                }
+            }
+        }
+    }
+
+    // `if (a = b)` is usually a typo for `==`; parentheses around the assignment mark it as intended
+    fn condition(&mut self, condition: &Expr) {
+        if let Expr::Assign { name, .. } = condition {
+            self.lint(LintCode::AssignmentInCondition, name, format!("Assignment to '{}' used as a condition; did you mean '=='?", name.lexeme));
+        }
+        self.expr(condition);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign { value, .. } => self.expr(value),
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Call { callee, arguments, .. } => {
+                self.check_arity(callee, arguments.len());
+                self.expr(callee);
+                for argument in arguments {
+                    self.expr(argument);
+                }
+            }
+            Expr::Get { object, .. } => self.expr(object),
+            Expr::Grouping { expression } => self.expr(expression),
+            Expr::Index { object, index, .. } => {
+                self.expr(object);
+                self.expr(index);
+            }
+            Expr::List { elements, .. } => {
+                for element in elements {
+                    self.expr(element);
+                }
+            }
+            Expr::Map { entries, .. } => {
+                for (key, value) in entries {
+                    self.expr(key);
+                    self.expr(value);
+                }
+            }
+            Expr::Set { object, value, .. } => {
+                self.expr(object);
+                self.expr(value);
+            }
+            Expr::SetIndex { object, index, value, .. } => {
+                self.expr(object);
+                self.expr(index);
+                self.expr(value);
+            }
+            Expr::Unary { right, .. } => self.expr(right),
+            Expr::Unpack { targets, value, .. } => {
+                for target in targets {
+                    self.expr(target);
+                }
+                self.expr(value);
+            }
+            Expr::Literal { .. } | Expr::Super { .. } | Expr::This { .. } | Expr::Variable { .. } => (),
+        }
+    }
+
+    fn check_arity(&mut self, callee: &Expr, count: usize) {
+        let Expr::Variable { name, id } = callee else {
+            return;
+        };
+        match self.known_arities.get(&name.lexeme) {
+            Some(arity) if !self.locals.contains_key(id) && !arity.accepts(count) => {
+                let message = format!("'{}' expects {arity} arguments but is called with {count}.", name.lexeme);
+                self.lint(LintCode::WrongArity, name, message);
+            }
+            _ => (),
+        }
+    }
+
+    // Found on the tokens, as blocks don't keep their braces; empty function bodies and match arms are left alone
+    fn empty_blocks(&mut self, tokens: &[Token]) {
+        let code: Vec<&Token> = tokens.iter().filter(|token| token.token_type != COMMENT).collect();
+        // Index in `code` of the first token of the pair, counted along the way
+        let mut next_position: usize = 0;
+        for pair in tokens.windows(2) {
+            let position = next_position;
+            if pair[0].token_type != COMMENT {
+                next_position += 1;
+            }
+            if pair[0].token_type != LEFT_BRACE || pair[1].token_type != RIGHT_BRACE {
+                continue;
+            }
+            let before = |offset: usize| position.checked_sub(offset).map(|index| code[index].token_type);
+            let is_block = match before(1) {
+                None | Some(ELSE | TRY | SEMICOLON | LEFT_BRACE | RIGHT_BRACE) => true,
+                // Statement heads like `if (...)`, but not the parameter lists of functions
+                Some(RIGHT_PAREN) => {
+                    let mut depth = 0;
+                    let mut open = position - 1;
+                    loop {
+                        match code[open].token_type {
+                            RIGHT_PAREN => depth += 1,
+                            LEFT_PAREN => depth -= 1,
+                            _ => (),
+                        }
+                        if depth == 0 || open == 0 {
+                            break;
+                        }
+                        open -= 1;
+                    }
+                    matches!(open.checked_sub(1).map(|index| code[index].token_type), Some(IF | WHILE | FOR | CATCH))
+                }
+                _ => false,
+            };
+            if is_block {
+                self.lint(LintCode::EmptyBlock, &pair[0], String::from("Empty block."));
+            }
+        }
+    }
+}
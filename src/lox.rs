@@ -1,21 +1,46 @@
+use std::cell::RefCell;
 use std::io::{self, Write};
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::rc::Rc;
 
+use crate::ast_printer::AstPrinter;
+use crate::callable::Arity;
+use crate::json;
+use crate::map::LoxMap;
 use crate::token::Token;
 use crate::token_type::TokenType;
 use crate::scanner::Scanner;
 use crate::parser::Parser;
 use crate::interpreter::{Interpreter, InterpreterError};
 use crate::resolver::Resolver;
+use crate::statement::Stmt;
+use crate::token_literal::TokenLiteral;
 
 static mut HAD_ERROR: bool = false;
 static mut HAD_RUNTIME_ERROR: bool = false;
+// Set once a script calls `exit(code)`
+static mut EXIT_CODE: Option<i32> = None;
 
-pub fn run_file(path: &str) {
+thread_local! {
+    // Where syntax errors and warnings go instead of stderr while `capture_diagnostics` runs
+    static DIAGNOSTICS: RefCell<Option<Vec<Diagnostic>>> = const { RefCell::new(None) };
+}
+
+/// A syntax error or warning as reported while compiling; the column is 0 when only the line is known
+pub struct Diagnostic {
+    pub line: i32,
+    pub column: i32,
+    pub length: usize,
+    pub message: String,
+    pub is_error: bool,
+}
+
+pub fn run_file(path: &str, script_args: &[String], allow_fs: bool) {
     match fs::read_to_string(path) {
         Ok(file_str) => {
-            let mut interpreter = Interpreter::new();
+            let mut interpreter = new_interpreter(script_args, allow_fs);
             run(&mut interpreter, file_str)
         },
         Err(err) => {
@@ -26,6 +51,9 @@ pub fn run_file(path: &str) {
         }
     }
     unsafe {
+        if let Some(code) = EXIT_CODE {
+            process::exit(code);
+        }
         if HAD_ERROR {
             process::exit(65);
         }
@@ -35,8 +63,90 @@ pub fn run_file(path: &str) {
     }
 }
 
-pub fn run_prompt() {
+pub fn print_ast(path: &str) {
+    let source = read_source(path);
     let mut interpreter = Interpreter::new();
+    match compile(&mut interpreter, source) {
+        Some(statements) => print!("{}", AstPrinter::new(&interpreter.locals).print(&statements)),
+        None => process::exit(65),
+    }
+}
+
+/// Prints every token the scanner produces, either as aligned columns or as one JSON object per line
+pub fn print_tokens(path: &str, as_json: bool) {
+    let mut scanner = Scanner::new(read_source(path));
+    for token in scanner.scan_tokens() {
+        if as_json {
+            println!("{}", token_json(&token));
+            continue;
+        }
+        let literal = match &token.literal {
+            TokenLiteral::LOX_NULL => String::new(),
+            TokenLiteral::LOX_STRING(string) => format!("\"{}\"", escape_controls(string)),
+            other => other.to_string(),
+        };
+        let position = format!("{}:{}", token.line, token.column);
+        let line = format!("{position:<8} {:<14} {:<16} {literal}", format!("{:?}", token.token_type), escape_controls(&token.lexeme));
+        println!("{}", line.trim_end());
+    }
+    if unsafe { HAD_ERROR } {
+        process::exit(65);
+    }
+}
+
+// Keeps each token on one line when a string literal spans several
+fn escape_controls(text: &str) -> String {
+    text.chars().map(|c| if c.is_control() { c.escape_debug().to_string() } else { c.to_string() }).collect()
+}
+
+fn token_json(token: &Token) -> String {
+    let mut map = LoxMap::new();
+    let mut field = |name: &str, value: TokenLiteral| map.insert(TokenLiteral::LOX_STRING(Rc::new(String::from(name))), value);
+    field("type", TokenLiteral::LOX_STRING(Rc::new(format!("{:?}", token.token_type))));
+    field("lexeme", TokenLiteral::LOX_STRING(Rc::new(token.lexeme.clone())));
+    field("literal", token.literal.clone());
+    field("line", TokenLiteral::LOX_INT(token.line as i64));
+    field("column", TokenLiteral::LOX_INT(token.column as i64));
+    json::stringify(&TokenLiteral::LOX_MAP(Rc::new(RefCell::new(map))), 0).unwrap()
+}
+
+/// The given files, along with the `.lox` files anywhere under the given directories, in a stable order
+pub fn lox_files(paths: &[String]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        collect_files(Path::new(path), &mut files);
+    }
+    files
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        files.push(path.to_path_buf());
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() || entry.extension().is_some_and(|extension| extension == "lox") {
+            collect_files(&entry, files);
+        }
+    }
+}
+
+// The source of a file for the dump modes, which exit straight away when it can't be read
+fn read_source(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(65);
+    })
+}
+
+pub fn run_prompt(allow_fs: bool) {
+    let mut interpreter = new_interpreter(&[], allow_fs);
     loop {
         print!("> ");
         io::stdout().flush().unwrap();
@@ -45,7 +155,10 @@ pub fn run_prompt() {
             Ok(n) => {
                 if n == 0 { break; }
                 run(&mut interpreter, buffer);
-                unsafe { HAD_ERROR = false; }
+                if let Some(code) = unsafe { EXIT_CODE } {
+                    process::exit(code);
+                }
+                clear_error();
             },
             Err(err) => {
                 eprintln!("{err}");
@@ -55,7 +168,28 @@ pub fn run_prompt() {
     }
 }
 
+/// An interpreter for the CLI, with the script's own arguments available through `args()`
+fn new_interpreter(script_args: &[String], allow_fs: bool) -> Interpreter {
+    let mut interpreter = Interpreter::new();
+    interpreter.allow_fs = allow_fs;
+    let script_args = script_args.to_vec();
+    interpreter.global_env.define_native("args", Arity::Exact(0), move |_, _| {
+        // A fresh list every call, so scripts can't change what later calls see
+        let args: Vec<TokenLiteral> = script_args.iter().map(|arg| TokenLiteral::LOX_STRING(Rc::new(arg.clone()))).collect();
+        Ok(TokenLiteral::LOX_LIST(Rc::new(RefCell::new(args))))
+    });
+    interpreter
+}
+
 pub fn run(interpreter: &mut Interpreter, source: String) {
+    if let Some(statements) = compile(interpreter, source) {
+        interpreter.interpret(&statements);
+    }
+}
+
+/// Scans, parses and resolves source without running it
+/// Errors are reported as they are found, and None is returned if there were any
+pub fn compile(interpreter: &mut Interpreter, source: String) -> Option<Vec<Stmt>> {
     let mut scanner = Scanner::new(source);
     let tokens = scanner.scan_tokens();
 
@@ -63,15 +197,15 @@ pub fn run(interpreter: &mut Interpreter, source: String) {
     let statements = parser.parse();
 
     // Return early if parsing fails
-    if statements.is_err() { return; }
+    if statements.is_err() { return None; }
 
     let statements = statements.unwrap();
     let mut resolver = Resolver::new(interpreter);
     resolver.resolve_statements(&statements);
 
-    if unsafe { HAD_ERROR } { return; }
+    if unsafe { HAD_ERROR } { return None; }
 
-    interpreter.interpret(&statements);
+    Some(statements)
 }
 
 pub fn error(line: i32, message: &str) {
@@ -79,14 +213,55 @@ pub fn error(line: i32, message: &str) {
 }
 
 pub fn token_error(token: &Token, message: &str) {
+    let captured = capture(token, message, true);
     if token.token_type == TokenType::EOF {
-        report(token.line, "at end", message);
+        fail_compile(token.line, "at end", message, captured);
     }
     else {
-        report(token.line, &format!("at '{}'", token.lexeme), message);
+        fail_compile(token.line, &format!("at '{}'", token.lexeme), message, captured);
+    }
+}
+
+pub fn token_warning(token: &Token, message: &str) {
+    // Warnings are reported like syntax errors but never stop the script from running
+    if !capture(token, message, false) {
+        eprintln!("[line {}] Warning: at '{}' -- {message}", token.line, token.lexeme);
     }
 }
 
+/// Runs `f` with syntax errors and warnings collected instead of printed, returning them along with its result
+pub fn capture_diagnostics<T>(f: impl FnOnce() -> T) -> (T, Vec<Diagnostic>) {
+    DIAGNOSTICS.with(|diagnostics| diagnostics.replace(Some(Vec::new())));
+    let result = f();
+    let diagnostics = DIAGNOSTICS.with(|diagnostics| diagnostics.take()).unwrap_or_default();
+    (result, diagnostics)
+}
+
+// Whether the diagnostic went to the capture sink rather than to be printed
+fn capture(token: &Token, message: &str, is_error: bool) -> bool {
+    DIAGNOSTICS.with(|diagnostics| match diagnostics.borrow_mut().as_mut() {
+        Some(diagnostics) => {
+            let length = token.lexeme.chars().count();
+            diagnostics.push(Diagnostic { line: token.line, column: token.column, length, message: String::from(message), is_error });
+            true
+        }
+        None => false,
+    })
+}
+
+pub fn had_error() -> bool {
+    unsafe { HAD_ERROR }
+}
+
+/// Forgets earlier syntax errors, for hosts that carry on with more source afterwards
+pub fn clear_error() {
+    unsafe { HAD_ERROR = false; }
+}
+
+pub fn request_exit(code: i32) {
+    unsafe { EXIT_CODE = Some(code); }
+}
+
 pub fn runtime_error(error: &InterpreterError) {
     match error {
         InterpreterError::OperatorError { line, err_msg } => {
@@ -99,6 +274,15 @@ pub fn runtime_error(error: &InterpreterError) {
 }
 
 pub fn report(line: i32, loc: &str, message: &str) {
-    eprintln!("[line {line}] Syntax Error: {loc} -- {message}");
+    let token = Token::new(TokenType::EOF, String::new(), TokenLiteral::LOX_NULL, line, 0);
+    let captured = capture(&token, message, true);
+    fail_compile(line, loc, message, captured);
+}
+
+// Prints the error unless the capture sink took it; either way the source won't run
+fn fail_compile(line: i32, loc: &str, message: &str, captured: bool) {
+    if !captured {
+        eprintln!("[line {line}] Syntax Error: {loc} -- {message}");
+    }
     unsafe { HAD_ERROR = true; }
 }
\ No newline at end of file
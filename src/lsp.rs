@@ -0,0 +1,441 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+use crate::callable::Arity;
+use crate::environment::Environment;
+use crate::expression::Expr;
+use crate::interpreter::Interpreter;
+use crate::json;
+use crate::linter;
+use crate::lox::{self, Diagnostic};
+use crate::map::LoxMap;
+use crate::parser::Parser;
+use crate::resolver::{Resolver, Symbol};
+use crate::scanner::Scanner;
+use crate::statement::Stmt;
+use crate::token::Token;
+use crate::token_literal::TokenLiteral::{self, LOX_BOOL, LOX_INT, LOX_LIST, LOX_MAP, LOX_NULL, LOX_STRING};
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+// LSP enumerations, see the specification for the full lists
+const SYNC_FULL: i64 = 1;
+const SEVERITY_ERROR: i64 = 1;
+const SEVERITY_WARNING: i64 = 2;
+const SYMBOL_CLASS: i64 = 5;
+const SYMBOL_METHOD: i64 = 6;
+const SYMBOL_ENUM: i64 = 10;
+const SYMBOL_FUNCTION: i64 = 12;
+const SYMBOL_VARIABLE: i64 = 13;
+const SYMBOL_CONSTANT: i64 = 14;
+const SYMBOL_ENUM_MEMBER: i64 = 22;
+
+/// Serves the Language Server Protocol over stdin and stdout until the client sends `exit`
+/// Documents are synced in full on every change, and checked with the scanner, parser, resolver and linter
+/// Returns the process exit code: 0 when the client asked for a shutdown first, as the protocol requires,
+/// and 1 when the client goes away without `exit`
+pub fn run_lsp() -> i32 {
+    let mut server = Server { documents: HashMap::new(), natives: Environment::native_arities(), shutdown: false };
+    let mut input = io::stdin().lock();
+    loop {
+        let Some(body) = read_message(&mut input) else {
+            return 1;
+        };
+        let handled = match json::parse(&body) {
+            Ok(message) => server.handle(&message),
+            Err(err) => send(response(LOX_NULL, Err((PARSE_ERROR, err.to_string())))).map(|_| None),
+        };
+        match handled {
+            Ok(Some(code)) => return code,
+            Ok(None) => (),
+            // Output only fails once the client has closed its end
+            Err(_) => return 1,
+        }
+    }
+}
+
+// The body of the next message, or None once the client has gone away
+fn read_message(input: &mut impl BufRead) -> Option<String> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let mut body = vec![0; length?];
+    input.read_exact(&mut body).ok()?;
+    String::from_utf8(body).ok()
+}
+
+fn send(message: TokenLiteral) -> io::Result<()> {
+    let body = json::stringify(&message, 0).unwrap();
+    let mut stdout = io::stdout().lock();
+    write!(stdout, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    stdout.flush()
+}
+
+fn response(id: TokenLiteral, result: Result<TokenLiteral, (i64, String)>) -> TokenLiteral {
+    match result {
+        Ok(result) => object(vec![("jsonrpc", string("2.0")), ("id", id), ("result", result)]),
+        Err((code, message)) => {
+            let error = object(vec![("code", LOX_INT(code)), ("message", string(&message))]);
+            object(vec![("jsonrpc", string("2.0")), ("id", id), ("error", error)])
+        }
+    }
+}
+
+fn notification(method: &str, params: TokenLiteral) -> TokenLiteral {
+    object(vec![("jsonrpc", string("2.0")), ("method", string(method)), ("params", params)])
+}
+
+fn object(fields: Vec<(&str, TokenLiteral)>) -> TokenLiteral {
+    let mut map = LoxMap::new();
+    for (name, value) in fields {
+        map.insert(string(name), value);
+    }
+    LOX_MAP(Rc::new(RefCell::new(map)))
+}
+
+fn list(items: Vec<TokenLiteral>) -> TokenLiteral {
+    LOX_LIST(Rc::new(RefCell::new(items)))
+}
+
+fn string(text: &str) -> TokenLiteral {
+    LOX_STRING(Rc::new(String::from(text)))
+}
+
+// Follows the path of keys down nested objects, giving nil when any of them is missing
+fn field(value: &TokenLiteral, path: &[&str]) -> TokenLiteral {
+    path.iter().fold(value.clone(), |value, key| match value {
+        LOX_MAP(map) => map.borrow().get(&string(key)).unwrap_or(LOX_NULL),
+        _ => LOX_NULL,
+    })
+}
+
+fn text_field(value: &TokenLiteral, path: &[&str]) -> String {
+    match field(value, path) {
+        LOX_STRING(text) => text.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn int_field(value: &TokenLiteral, path: &[&str]) -> i32 {
+    match field(value, path) {
+        LOX_INT(int) => int as i32,
+        _ => 0,
+    }
+}
+
+// LSP positions are 0-based where tokens are 1-based; columns count characters, which matches the
+// UTF-16 offsets clients use everywhere outside the astral planes
+fn position(line: i32, column: i32) -> TokenLiteral {
+    object(vec![("line", LOX_INT((line - 1).max(0) as i64)), ("character", LOX_INT((column - 1).max(0) as i64))])
+}
+
+fn range(line: i32, column: i32, length: usize) -> TokenLiteral {
+    object(vec![("start", position(line, column)), ("end", position(line, column + length as i32))])
+}
+
+fn location(uri: &str, line: i32, column: i32, length: usize) -> TokenLiteral {
+    object(vec![("uri", string(uri)), ("range", range(line, column, length))])
+}
+
+struct Server {
+    documents: HashMap<String, Analysis>,
+    natives: HashMap<String, Arity>,
+    shutdown: bool,
+}
+
+// What is known about an open document, from the last version of it that parsed
+struct Analysis {
+    statements: Vec<Stmt>,
+    symbols: Vec<Symbol>,
+}
+
+impl Server {
+    // Handles one message, returning the exit code once the client sends `exit`
+    fn handle(&mut self, message: &TokenLiteral) -> io::Result<Option<i32>> {
+        let method = text_field(message, &["method"]);
+        let params = field(message, &["params"]);
+        let result = match method.as_str() {
+            "initialize" => Ok(Server::capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(LOX_NULL)
+            }
+            "exit" => return Ok(Some(if self.shutdown { 0 } else { 1 })),
+            "textDocument/didOpen" => {
+                self.update(&text_field(&params, &["textDocument", "uri"]), &text_field(&params, &["textDocument", "text"]))?;
+                return Ok(None);
+            }
+            "textDocument/didChange" => {
+                // With full sync the last change holds the whole text
+                if let LOX_LIST(changes) = field(&params, &["contentChanges"]) {
+                    if let Some(change) = changes.borrow().last() {
+                        self.update(&text_field(&params, &["textDocument", "uri"]), &text_field(change, &["text"]))?;
+                    }
+                }
+                return Ok(None);
+            }
+            "textDocument/didClose" => {
+                let uri = text_field(&params, &["textDocument", "uri"]);
+                self.documents.remove(&uri);
+                Server::publish(&uri, Vec::new())?;
+                return Ok(None);
+            }
+            "textDocument/definition" => Ok(self.definition(&params)),
+            "textDocument/references" => Ok(self.references(&params)),
+            "textDocument/hover" => Ok(self.hover(&params)),
+            "textDocument/documentSymbol" => Ok(self.document_symbols(&params)),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{method}'."))),
+        };
+
+        // Notifications never get a response, even for methods the server doesn't know
+        let id = field(message, &["id"]);
+        if !matches!(id, LOX_NULL) {
+            send(response(id, result))?;
+        }
+        Ok(None)
+    }
+
+    fn capabilities() -> TokenLiteral {
+        let capabilities = object(vec![
+            ("textDocumentSync", LOX_INT(SYNC_FULL)),
+            ("definitionProvider", LOX_BOOL(true)),
+            ("referencesProvider", LOX_BOOL(true)),
+            ("hoverProvider", LOX_BOOL(true)),
+            ("documentSymbolProvider", LOX_BOOL(true)),
+        ]);
+        object(vec![("capabilities", capabilities), ("serverInfo", object(vec![("name", string("rlox"))]))])
+    }
+
+    // Re-checks a document and publishes its diagnostics; navigation keeps using the last version that parsed
+    fn update(&mut self, uri: &str, source: &str) -> io::Result<()> {
+        lox::clear_error();
+        let (analysis, mut diagnostics) = lox::capture_diagnostics(|| {
+            let statements = Parser::new(Scanner::new(source.to_string()).scan_tokens()).parse().ok()?;
+            let mut interpreter = Interpreter::new();
+            let mut resolver = Resolver::new(&mut interpreter);
+            resolver.resolve_statements(&statements);
+            Some(Analysis { statements, symbols: resolver.symbols() })
+        });
+
+        let mut lints = Vec::new();
+        if !lox::had_error() {
+            lints = lox::capture_diagnostics(|| linter::lint_source(source)).0.unwrap_or_default();
+        }
+        let lints = lints.into_iter().map(|lint| (Some(lint.code.name()), Diagnostic {
+            line: lint.line,
+            column: lint.column,
+            length: lint.length,
+            message: lint.message,
+            is_error: false,
+        }));
+        let mut all: Vec<(Option<&str>, Diagnostic)> = diagnostics.drain(..).map(|diagnostic| (None, diagnostic)).collect();
+        all.extend(lints);
+
+        if let Some(analysis) = analysis {
+            self.documents.insert(String::from(uri), analysis);
+        }
+        lox::clear_error();
+        Server::publish(uri, all)
+    }
+
+    fn publish(uri: &str, diagnostics: Vec<(Option<&str>, Diagnostic)>) -> io::Result<()> {
+        let diagnostics = diagnostics.into_iter().map(|(code, diagnostic)| {
+            // Errors only known by line cover the whole of it
+            let range = match diagnostic.column {
+                0 => object(vec![("start", position(diagnostic.line, 1)), ("end", position(diagnostic.line + 1, 1))]),
+                column => range(diagnostic.line, column, diagnostic.length.max(1)),
+            };
+            let severity = if diagnostic.is_error { SEVERITY_ERROR } else { SEVERITY_WARNING };
+            let mut fields = vec![("range", range), ("severity", LOX_INT(severity)), ("source", string("rlox")), ("message", string(&diagnostic.message))];
+            fields.extend(code.map(|code| ("code", string(code))));
+            object(fields)
+        }).collect();
+        send(notification("textDocument/publishDiagnostics", object(vec![("uri", string(uri)), ("diagnostics", list(diagnostics))])))
+    }
+
+    // The document and the symbol under the cursor of a positional request
+    fn symbol_at(&self, params: &TokenLiteral) -> Option<(&Analysis, &Symbol)> {
+        let analysis = self.documents.get(&text_field(params, &["textDocument", "uri"]))?;
+        let line = int_field(params, &["position", "line"]) + 1;
+        let column = int_field(params, &["position", "character"]) + 1;
+        // A cursor just past the end of a name still points at it
+        let symbol = analysis.symbols.iter().find(|symbol| {
+            symbol.line == line && (symbol.column..=symbol.column + symbol.name.chars().count() as i32).contains(&column)
+        })?;
+        Some((analysis, symbol))
+    }
+
+    fn definition(&self, params: &TokenLiteral) -> TokenLiteral {
+        let uri = text_field(params, &["textDocument", "uri"]);
+        match self.symbol_at(params) {
+            Some((_, Symbol { name, declaration: Some((line, column)), .. })) => location(&uri, *line, *column, name.chars().count()),
+            _ => LOX_NULL,
+        }
+    }
+
+    // Uses of natives and undeclared globals have no declaration, so they are matched by name instead
+    fn references(&self, params: &TokenLiteral) -> TokenLiteral {
+        let uri = text_field(params, &["textDocument", "uri"]);
+        let include_declaration = matches!(field(params, &["context", "includeDeclaration"]), LOX_BOOL(true));
+        let Some((analysis, target)) = self.symbol_at(params) else {
+            return LOX_NULL;
+        };
+        let locations = analysis.symbols.iter()
+            .filter(|symbol| symbol.declaration == target.declaration && (target.declaration.is_some() || symbol.name == target.name))
+            .filter(|symbol| include_declaration || symbol.declaration != Some((symbol.line, symbol.column)))
+            .map(|symbol| location(&uri, symbol.line, symbol.column, symbol.name.chars().count()))
+            .collect();
+        list(locations)
+    }
+
+    fn hover(&self, params: &TokenLiteral) -> TokenLiteral {
+        let Some((analysis, symbol)) = self.symbol_at(params) else {
+            return LOX_NULL;
+        };
+        let text = match symbol.declaration {
+            Some(declaration) => {
+                let mut signatures = HashMap::new();
+                Server::signatures(&analysis.statements, &mut signatures);
+                match signatures.remove(&declaration) {
+                    Some(signature) => format!("```lox\n{signature}\n```"),
+                    None => return LOX_NULL,
+                }
+            }
+            None => match self.natives.get(&symbol.name) {
+                Some(arity) => format!("```lox\nfun {}\n```\nNative function taking {arity} arguments", symbol.name),
+                None => return LOX_NULL,
+            },
+        };
+        let contents = object(vec![("kind", string("markdown")), ("value", string(&text))]);
+        object(vec![("contents", contents), ("range", range(symbol.line, symbol.column, symbol.name.chars().count()))])
+    }
+
+    // How each declaration reads in a hover, by the line and column of its name
+    fn signatures(statements: &[Stmt], signatures: &mut HashMap<(i32, i32), String>) {
+        for statement in statements {
+            match statement {
+                Stmt::Block { statements } => Server::signatures(statements, signatures),
+                Stmt::Class { name, superclass, methods } => {
+                    let mut signature = format!("class {}", name.lexeme);
+                    if let Some(Expr::Variable { name: superclass, .. }) = superclass.as_deref() {
+                        signature = format!("{signature} < {}", superclass.lexeme);
+                    }
+                    Server::declare(signatures, name, signature);
+                    Server::signatures(methods, signatures);
+                }
+                Stmt::Enum { name, variants } => {
+                    let variants: Vec<String> = variants.iter().map(|variant| match variant.fields.is_empty() {
+                        true => variant.name.lexeme.clone(),
+                        false => format!("{}{}", variant.name.lexeme, parameters(&variant.fields)),
+                    }).collect();
+                    Server::declare(signatures, name, format!("enum {} {{ {} }}", name.lexeme, variants.join(", ")));
+                }
+                Stmt::ForIn { name, body, .. } => {
+                    Server::declare(signatures, name, format!("var {}", name.lexeme));
+                    Server::signatures(std::slice::from_ref(body), signatures);
+                }
+                Stmt::Function { ptr } => {
+                    Server::declare(signatures, &ptr.name, format!("fun {}{}", ptr.name.lexeme, parameters(&ptr.params)));
+                    for param in &ptr.params {
+                        Server::declare(signatures, param, format!("(parameter) {}", param.lexeme));
+                    }
+                    Server::signatures(&ptr.body, signatures);
+                }
+                Stmt::If { then_branch, else_branch, .. } => {
+                    Server::signatures(std::slice::from_ref(then_branch), signatures);
+                    Server::signatures(std::slice::from_ref(else_branch), signatures);
+                }
+                Stmt::Match { arms, .. } => {
+                    for arm in arms {
+                        for binding in arm.pattern.bindings() {
+                            Server::declare(signatures, binding, format!("var {}", binding.lexeme));
+                        }
+                        Server::signatures(std::slice::from_ref(&arm.body), signatures);
+                    }
+                }
+                Stmt::Try { body, name, handler } => {
+                    Server::declare(signatures, name, format!("var {}", name.lexeme));
+                    Server::signatures(std::slice::from_ref(body), signatures);
+                    Server::signatures(std::slice::from_ref(handler), signatures);
+                }
+                Stmt::Var { target, is_const, .. } => {
+                    let keyword = if *is_const { "const" } else { "var" };
+                    for name in target.names() {
+                        Server::declare(signatures, name, format!("{keyword} {}", name.lexeme));
+                    }
+                }
+                Stmt::While { body, .. } => Server::signatures(std::slice::from_ref(body), signatures),
+                _ => (),
+            }
+        }
+    }
+
+    fn declare(signatures: &mut HashMap<(i32, i32), String>, name: &Token, signature: String) {
+        signatures.insert((name.line, name.column), signature);
+    }
+
+    // The classes, functions, enums and variables declared at the top level, with methods and variants nested inside
+    fn document_symbols(&self, params: &TokenLiteral) -> TokenLiteral {
+        let Some(analysis) = self.documents.get(&text_field(params, &["textDocument", "uri"])) else {
+            return LOX_NULL;
+        };
+        let symbol = |name: &Token, detail: String, kind: i64, children: Vec<TokenLiteral>| {
+            let range = range(name.line, name.column, name.lexeme.chars().count());
+            object(vec![
+                ("name", string(&name.lexeme)),
+                ("detail", string(&detail)),
+                ("kind", LOX_INT(kind)),
+                ("range", range.clone()),
+                ("selectionRange", range),
+                ("children", list(children)),
+            ])
+        };
+
+        let mut symbols = Vec::new();
+        for statement in &analysis.statements {
+            match statement {
+                Stmt::Class { name, methods, .. } => {
+                    let methods = methods.iter().filter_map(|method| match method {
+                        Stmt::Function { ptr } => Some(symbol(&ptr.name, parameters(&ptr.params), SYMBOL_METHOD, Vec::new())),
+                        _ => None,
+                    }).collect();
+                    symbols.push(symbol(name, String::new(), SYMBOL_CLASS, methods));
+                }
+                Stmt::Enum { name, variants } => {
+                    let variants = variants.iter()
+                        .map(|variant| symbol(&variant.name, String::new(), SYMBOL_ENUM_MEMBER, Vec::new()))
+                        .collect();
+                    symbols.push(symbol(name, String::new(), SYMBOL_ENUM, variants));
+                }
+                Stmt::Function { ptr } => symbols.push(symbol(&ptr.name, parameters(&ptr.params), SYMBOL_FUNCTION, Vec::new())),
+                Stmt::Var { target, is_const, .. } => {
+                    let kind = if *is_const { SYMBOL_CONSTANT } else { SYMBOL_VARIABLE };
+                    symbols.extend(target.names().into_iter().map(|name| symbol(name, String::new(), kind, Vec::new())));
+                }
+                _ => (),
+            }
+        }
+        list(symbols)
+    }
+}
+
+fn parameters(params: &[Token]) -> String {
+    format!("({})", params.iter().map(|param| param.lexeme.as_str()).collect::<Vec<_>>().join(", "))
+}
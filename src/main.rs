@@ -1,40 +1,83 @@
-mod token_type;
-mod token;
-mod scanner;
-mod token_literal;
-mod lox;
-mod parser;
-mod expression;
-mod interpreter;
-mod statement;
-mod environment;
-mod callable;
-mod function;
-mod clock;
-mod function_object;
-mod resolver;
-mod class;
-mod class_instance;
-mod native_function;
-
 use std::env;
-use std::cmp::Ordering;
-use std::process;
+use std::process::exit;
 
-use lox::{run_file, run_prompt};
+use rlox::{formatter, linter, lsp, testing};
+use rlox::lox::{print_ast, print_tokens, run_file, run_prompt};
 
-const ARGS_LIMIT: usize = 2;
+// Grants scripts access to the filesystem, which is denied by default
+const ALLOW_FS_FLAG: &str = "--allow-fs";
+// Prints the resolved syntax tree of the script instead of running it
+const PRINT_AST_FLAG: &str = "--print-ast";
+// Print the tokens of the script instead of running it, as text or as JSON lines
+const TOKENS_FLAG: &str = "--tokens";
+const TOKENS_JSON_FLAG: &str = "--tokens-json";
+// Runs the `test_*` functions of the given files instead of a script
+const TEST_COMMAND: &str = "test";
+// Rewrites the given files in the canonical style, or with `--check` only lists the ones that differ
+const FMT_COMMAND: &str = "fmt";
+const CHECK_FLAG: &str = "--check";
+// Reports likely mistakes in the given files without running them
+const LINT_COMMAND: &str = "lint";
+// Serves the Language Server Protocol on stdin and stdout, for editors
+// Clients often pass `--stdio` to pick that transport, which is the only one, so it is accepted and ignored
+const LSP_COMMAND: &str = "lsp";
+const STDIO_FLAG: &str = "--stdio";
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // Flags go before the script; everything after the script is passed on to it
+    let flags = args.iter().take_while(|arg| arg.starts_with("--")).count();
+    let (flags, rest) = args.split_at(flags);
+    if let Some(flag) = flags.iter().find(|flag| ![ALLOW_FS_FLAG, PRINT_AST_FLAG, TOKENS_FLAG, TOKENS_JSON_FLAG].contains(&flag.as_str())) {
+        eprintln!("unknown flag '{flag}'");
+        usage();
+    }
+    let has_flag = |name: &str| flags.iter().any(|flag| flag == name);
+    let allow_fs = has_flag(ALLOW_FS_FLAG);
 
-    match args.len().cmp(&ARGS_LIMIT) {
-        Ordering::Greater => {
-            println!("usage: rlox script");
-            process::exit(64);
-        },
-        Ordering::Equal => run_file(&args[1]),
-        Ordering::Less => run_prompt(),
+    match rest.split_first() {
+        Some((command, paths)) if command == TEST_COMMAND => {
+            if paths.is_empty() {
+                usage();
+            }
+            exit(testing::run_tests(paths, allow_fs));
+        }
+        Some((command, paths)) if command == FMT_COMMAND => {
+            let check = paths.first().is_some_and(|path| path == CHECK_FLAG);
+            let paths = if check { &paths[1..] } else { paths };
+            if paths.is_empty() {
+                usage();
+            }
+            exit(formatter::run_fmt(paths, check));
+        }
+        Some((command, paths)) if command == LINT_COMMAND => {
+            if paths.is_empty() {
+                usage();
+            }
+            exit(linter::run_lint(paths));
+        }
+        Some((command, options)) if command == LSP_COMMAND => {
+            if options.iter().any(|option| option != STDIO_FLAG) {
+                usage();
+            }
+            exit(lsp::run_lsp());
+        }
+        Some((script, _)) if has_flag(PRINT_AST_FLAG) => print_ast(script),
+        Some((script, _)) if has_flag(TOKENS_FLAG) || has_flag(TOKENS_JSON_FLAG) => print_tokens(script, has_flag(TOKENS_JSON_FLAG)),
+        Some((script, script_args)) => run_file(script, script_args, allow_fs),
+        None => run_prompt(allow_fs),
     }
 }
 
+fn usage() -> ! {
+    println!("usage: rlox [{ALLOW_FS_FLAG}] [script [args...]]");
+    println!("       rlox [{ALLOW_FS_FLAG}] {TEST_COMMAND} path...");
+    println!("       rlox {FMT_COMMAND} [{CHECK_FLAG}] path...");
+    println!("       rlox {LINT_COMMAND} path...");
+    println!("       rlox {LSP_COMMAND} [{STDIO_FLAG}]");
+    println!("       rlox {PRINT_AST_FLAG} script");
+    println!("       rlox {TOKENS_FLAG} | {TOKENS_JSON_FLAG} script");
+    exit(64);
+}
+
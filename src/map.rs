@@ -0,0 +1,65 @@
+use std::fmt::{Display, Formatter};
+
+use crate::interpreter::Interpreter;
+use crate::token_literal::TokenLiteral;
+
+// Entries are kept in insertion order so that printing and iterating a map is deterministic
+#[derive(Default)]
+pub struct LoxMap {
+    entries: Vec<(TokenLiteral, TokenLiteral)>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Only values compared by content can be keys; identity-compared values would never be found again
+    pub fn is_valid_key(key: &TokenLiteral) -> bool {
+        match key {
+            TokenLiteral::LOX_NUMBER(_) | TokenLiteral::LOX_INT(_) | TokenLiteral::LOX_BIGINT(_) | TokenLiteral::LOX_STRING(_) | TokenLiteral::LOX_BOOL(_) | TokenLiteral::LOX_NULL => true,
+            TokenLiteral::LOX_ENUM_VALUE(value) => value.values.iter().all(LoxMap::is_valid_key),
+            _ => false
+        }
+    }
+
+    pub fn get(&self, key: &TokenLiteral) -> Option<TokenLiteral> {
+        self.position(key).map(|i| self.entries[i].1.clone())
+    }
+
+    pub fn insert(&mut self, key: TokenLiteral, value: TokenLiteral) {
+        match self.position(&key) {
+            Some(i) => self.entries[i].1 = value,
+            None => self.entries.push((key, value)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &(TokenLiteral, TokenLiteral)> {
+        self.entries.iter()
+    }
+
+    pub fn keys(&self) -> Vec<TokenLiteral> {
+        self.entries.iter().map(|(key, _)| key.clone()).collect()
+    }
+
+    fn position(&self, key: &TokenLiteral) -> Option<usize> {
+        self.entries.iter().position(|(existing, _)| Interpreter::is_equal(existing.clone(), key.clone()))
+    }
+}
+
+impl Display for LoxMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{")?;
+        for (i, (key, value)) in self.entries.iter().enumerate() {
+            if i > 0 { write!(f, ", ")?; }
+            key.fmt_nested(f)?;
+            write!(f, ": ")?;
+            value.fmt_nested(f)?;
+        }
+        write!(f, "}}")
+    }
+}
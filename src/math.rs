@@ -0,0 +1,177 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::bigint::BigInt;
+use crate::callable::Arity;
+use crate::interpreter::InterpreterError;
+use crate::token_literal::TokenLiteral::{self, LOX_BIGINT, LOX_BOOL, LOX_INT, LOX_NUMBER};
+
+// Largest integer `pow` will build, so a huge exponent fails with an error instead of running for hours
+const MAX_POW_BITS: u64 = 1 << 20;
+
+// Math natives, accepting integers, big integers and floats alike
+#[derive(Clone, Copy)]
+pub enum MathNative {
+    Sqrt,
+    Pow,
+    Abs,
+    Floor,
+    Ceil,
+    Round,
+    Sin,
+    Cos,
+    Tan,
+    Atan2,
+    Exp,
+    Log,
+    Min,
+    Max,
+    IsNaN,
+    IsInfinite,
+}
+
+impl MathNative {
+    pub const ALL: [MathNative; 16] = [
+        MathNative::Sqrt,
+        MathNative::Pow,
+        MathNative::Abs,
+        MathNative::Floor,
+        MathNative::Ceil,
+        MathNative::Round,
+        MathNative::Sin,
+        MathNative::Cos,
+        MathNative::Tan,
+        MathNative::Atan2,
+        MathNative::Exp,
+        MathNative::Log,
+        MathNative::Min,
+        MathNative::Max,
+        MathNative::IsNaN,
+        MathNative::IsInfinite,
+    ];
+
+    // Constants defined alongside the functions
+    pub const CONSTANTS: [(&'static str, f64); 2] = [("PI", std::f64::consts::PI), ("E", std::f64::consts::E)];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MathNative::Sqrt => "sqrt",
+            MathNative::Pow => "pow",
+            MathNative::Abs => "abs",
+            MathNative::Floor => "floor",
+            MathNative::Ceil => "ceil",
+            MathNative::Round => "round",
+            MathNative::Sin => "sin",
+            MathNative::Cos => "cos",
+            MathNative::Tan => "tan",
+            MathNative::Atan2 => "atan2",
+            MathNative::Exp => "exp",
+            MathNative::Log => "log",
+            MathNative::Min => "min",
+            MathNative::Max => "max",
+            MathNative::IsNaN => "isNaN",
+            MathNative::IsInfinite => "isInfinite",
+        }
+    }
+
+    pub fn arity(&self) -> Arity {
+        match self {
+            MathNative::Pow | MathNative::Atan2 => Arity::Exact(2),
+            MathNative::Min | MathNative::Max => Arity::AtLeast(1),
+            _ => Arity::Exact(1),
+        }
+    }
+
+    pub fn call(&self, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match self {
+            MathNative::Sqrt => Ok(LOX_NUMBER(self.float(&arguments, 0)?.sqrt())),
+            MathNative::Pow => self.pow(&arguments),
+            MathNative::Abs => match &arguments[0] {
+                LOX_INT(value) => match value.checked_abs() {
+                    Some(value) => Ok(LOX_INT(value)),
+                    None => Ok(BigInt::from_i64(*value).neg().into_literal()),
+                },
+                LOX_BIGINT(value) if *value.as_ref() < BigInt::from_i64(0) => Ok(value.neg().into_literal()),
+                LOX_BIGINT(value) => Ok(LOX_BIGINT(Rc::clone(value))),
+                _ => Ok(LOX_NUMBER(self.float(&arguments, 0)?.abs())),
+            },
+            MathNative::Floor => self.round_to_integer(&arguments, f64::floor),
+            MathNative::Ceil => self.round_to_integer(&arguments, f64::ceil),
+            MathNative::Round => self.round_to_integer(&arguments, f64::round),
+            MathNative::Sin => Ok(LOX_NUMBER(self.float(&arguments, 0)?.sin())),
+            MathNative::Cos => Ok(LOX_NUMBER(self.float(&arguments, 0)?.cos())),
+            MathNative::Tan => Ok(LOX_NUMBER(self.float(&arguments, 0)?.tan())),
+            MathNative::Atan2 => Ok(LOX_NUMBER(self.float(&arguments, 0)?.atan2(self.float(&arguments, 1)?))),
+            MathNative::Exp => Ok(LOX_NUMBER(self.float(&arguments, 0)?.exp())),
+            MathNative::Log => Ok(LOX_NUMBER(self.float(&arguments, 0)?.ln())),
+            MathNative::Min | MathNative::Max => {
+                let wanted = match self {
+                    MathNative::Min => Ordering::Less,
+                    _ => Ordering::Greater,
+                };
+                // A lone argument still has to be a number
+                self.float(&arguments, 0)?;
+                let mut best = 0;
+                for i in 1..arguments.len() {
+                    if self.compare(&arguments, i, best)? == wanted {
+                        best = i;
+                    }
+                }
+                Ok(arguments[best].clone())
+            }
+            MathNative::IsNaN => Ok(LOX_BOOL(self.float(&arguments, 0)?.is_nan())),
+            MathNative::IsInfinite => Ok(LOX_BOOL(self.float(&arguments, 0)?.is_infinite())),
+        }
+    }
+
+    /// Exact for integers raised to non-negative integer powers, a float otherwise
+    fn pow(&self, arguments: &[TokenLiteral]) -> Result<TokenLiteral, InterpreterError> {
+        match (BigInt::from_literal(&arguments[0]), &arguments[1]) {
+            (Some(base), LOX_INT(exponent)) if *exponent >= 0 => match u32::try_from(*exponent) {
+                // The result has at most `bits(base) * exponent` bits; 0, 1 and -1 stay that small whatever the exponent
+                Ok(exponent) if base.bits() <= 1 || base.bits() * exponent as u64 <= MAX_POW_BITS => Ok(base.pow(exponent).into_literal()),
+                Ok(_) => Err(self.error(format!("result would exceed the maximum integer size of {MAX_POW_BITS} bits."))),
+                Err(_) => Err(self.error(format!("exponent {exponent} is too large."))),
+            },
+            _ => Ok(LOX_NUMBER(self.float(arguments, 0)?.powf(self.float(arguments, 1)?))),
+        }
+    }
+
+    /// Rounds floats to an integer, integers are already whole
+    fn round_to_integer(&self, arguments: &[TokenLiteral], round: fn(f64) -> f64) -> Result<TokenLiteral, InterpreterError> {
+        match &arguments[0] {
+            LOX_INT(_) | LOX_BIGINT(_) => Ok(arguments[0].clone()),
+            _ => {
+                let value = self.float(arguments, 0)?;
+                match BigInt::from_f64(round(value)) {
+                    Some(value) => Ok(value.into_literal()),
+                    None => Err(self.error(format!("can't convert {value} to an integer."))),
+                }
+            }
+        }
+    }
+
+    /// Integers are compared exactly rather than through a lossy float conversion
+    fn compare(&self, arguments: &[TokenLiteral], left: usize, right: usize) -> Result<Ordering, InterpreterError> {
+        match (BigInt::from_literal(&arguments[left]), BigInt::from_literal(&arguments[right])) {
+            (Some(left), Some(right)) => Ok(left.cmp(&right)),
+            _ => {
+                let (left, right) = (self.float(arguments, left)?, self.float(arguments, right)?);
+                Ok(left.partial_cmp(&right).unwrap_or(Ordering::Equal))
+            }
+        }
+    }
+
+    fn error(&self, message: String) -> InterpreterError {
+        InterpreterError::NativeError(format!("{}() {message}", self.name()))
+    }
+
+    fn float(&self, arguments: &[TokenLiteral], position: usize) -> Result<f64, InterpreterError> {
+        match &arguments[position] {
+            LOX_NUMBER(value) => Ok(*value),
+            LOX_INT(value) => Ok(*value as f64),
+            LOX_BIGINT(value) => Ok(value.to_f64()),
+            other => Err(self.error(format!("expects a number as argument {} but got {other}.", position + 1))),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::any::Any;
+use std::rc::Rc;
+
+use crate::callable::Arity;
+use crate::class_instance::LoxInstance;
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::token_literal::TokenLiteral;
+
+// Signatures of the closures behind native classes
+// Constructors build the opaque handle that the new instance wraps, methods receive the instance they were called on
+pub type NativeConstructorFn = dyn Fn(&mut Interpreter, Vec<TokenLiteral>) -> Result<Rc<dyn Any>, InterpreterError>;
+pub type NativeMethodFn = dyn Fn(&mut Interpreter, &Rc<LoxInstance>, Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError>;
+
+pub struct NativeConstructor {
+    pub arity: Arity,
+    function: Box<NativeConstructorFn>,
+}
+
+impl NativeConstructor {
+    pub fn new<F>(arity: Arity, function: F) -> Self
+    where
+        F: Fn(&mut Interpreter, Vec<TokenLiteral>) -> Result<Rc<dyn Any>, InterpreterError> + 'static,
+    {
+        Self { arity, function: Box::new(function) }
+    }
+
+    pub fn call(&self, interpreter: &mut Interpreter, arguments: Vec<TokenLiteral>) -> Result<Rc<dyn Any>, InterpreterError> {
+        (self.function)(interpreter, arguments)
+    }
+}
+
+pub struct NativeMethod {
+    pub name: String,
+    pub arity: Arity,
+    function: Box<NativeMethodFn>,
+}
+
+impl NativeMethod {
+    pub fn new<F>(name: &str, arity: Arity, function: F) -> Self
+    where
+        F: Fn(&mut Interpreter, &Rc<LoxInstance>, Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> + 'static,
+    {
+        Self { name: String::from(name), arity, function: Box::new(function) }
+    }
+}
+
+// A native method looked up on an instance, ready to be called like any other function
+pub struct BoundNativeMethod {
+    instance: Rc<LoxInstance>,
+    pub method: Rc<NativeMethod>,
+}
+
+impl BoundNativeMethod {
+    pub fn new(instance: Rc<LoxInstance>, method: Rc<NativeMethod>) -> Self {
+        Self { instance, method }
+    }
+
+    pub fn call(&self, interpreter: &mut Interpreter, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        (self.method.function)(interpreter, &self.instance, arguments)
+    }
+}
@@ -1,21 +1,53 @@
-use crate::clock::Clock;
+use std::rc::Rc;
+
+use crate::callable::Arity;
+use crate::generator::Generator;
+use crate::native_class::BoundNativeMethod;
 use crate::interpreter::{Interpreter, InterpreterError};
 use crate::token_literal::TokenLiteral;
 
+// Signature of the closures behind host functions
+pub type HostFn = dyn Fn(&mut Interpreter, Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError>;
+
 pub enum NativeFunction {
-    NativeClock(Clock)
+    // `next` method of a generator, bound to the generator it resumes
+    GeneratorNext(Rc<Generator>),
+    // `done` method of a generator, telling a yielded nil apart from the nil `next` returns once finished
+    GeneratorDone(Rc<Generator>),
+    // Any function registered through `Environment::define_native`
+    Host(HostFunction),
+    // Method of a native class, bound to the instance it was looked up on
+    BoundMethod(BoundNativeMethod),
+}
+
+// A native implemented by a Rust closure, so that embedders can add functions without a new variant here
+pub struct HostFunction {
+    pub name: String,
+    arity: Arity,
+    function: Box<HostFn>,
+}
+
+impl HostFunction {
+    pub fn new(name: String, arity: Arity, function: Box<HostFn>) -> Self {
+        Self { name, arity, function }
+    }
 }
 
 impl NativeFunction {
-    pub fn call(&self, _interpreter: &mut Interpreter, _arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+    pub fn call(&self, interpreter: &mut Interpreter, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
         match self {
-            NativeFunction::NativeClock(_)=> Clock::time_since_epoch_as_secs()
+            NativeFunction::GeneratorNext(generator) => Ok(generator.resume(interpreter)?.unwrap_or(TokenLiteral::LOX_NULL)),
+            NativeFunction::GeneratorDone(generator) => Ok(TokenLiteral::LOX_BOOL(generator.is_done())),
+            NativeFunction::Host(host) => (host.function)(interpreter, arguments),
+            NativeFunction::BoundMethod(bound) => bound.call(interpreter, arguments),
         }
     }
 
-    pub fn arity(&self) -> usize {
+    pub fn arity(&self) -> Arity {
         match self {
-            NativeFunction::NativeClock(_) => Clock::arity()
+            NativeFunction::GeneratorNext(_) | NativeFunction::GeneratorDone(_) => Arity::Exact(0),
+            NativeFunction::Host(host) => host.arity,
+            NativeFunction::BoundMethod(bound) => bound.method.arity,
         }
     }
-}
\ No newline at end of file
+}
@@ -3,8 +3,9 @@ use std::rc::Rc;
 
 use crate::expression::Expr::{self, *};
 use crate::lox;
-use crate::statement::Stmt;
+use crate::statement::{EnumVariantDecl, Stmt, VarTarget};
 use crate::function_object::FunctionObject;
+use crate::pattern::{MatchArm, Pattern};
 use crate::token::Token;
 use crate::token_literal::TokenLiteral;
 use crate::token_type::TokenType::{self, *};
@@ -14,12 +15,14 @@ const FUNCTION_ARGUMENT_LIMIT: usize = 255;
 pub struct Parser {
     tokens: Vec<Token>,
     current: i32,
-    curr_id: usize
+    curr_id: usize,
+    // One entry per function being parsed, set once its body is found to contain a `yield`
+    function_yields: Vec<bool>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0, curr_id: 0 }
+        Self { tokens, current: 0, curr_id: 0, function_yields: Vec::new() }
     }
 
     pub fn parse(&mut self) -> Result<Vec<Stmt>, String> {
@@ -37,14 +40,22 @@ impl Parser {
         }
 
         if self.match_token(&[VAR]) {
-            return self.var_declaration();
+            return self.var_declaration(false);
+        }
+
+        if self.match_token(&[CONST]) {
+            return self.var_declaration(true);
         }
 
         if self.match_token(&[CLASS]) {
             return self.class_declaration();
         }
 
-        self.statement().map_err(|error| { self.synchronize(); error })
+        if self.match_token(&[ENUM]) {
+            return self.enum_declaration();
+        }
+
+        self.statement().inspect_err(|_| self.synchronize())
     }
 
     fn function_declaration(&mut self, function_type: String) -> Result<Stmt, String> {
@@ -66,18 +77,51 @@ impl Parser {
         }
         self.consume(RIGHT_PAREN, "Expect ')' after parameters.")?;
         self.consume(LEFT_BRACE, &format!("Expect '{{' before {function_type} body"))?;
-        let body = self.block_statement()?;
-        Ok(Stmt::Function { ptr: Rc::new(FunctionObject {name, params: parameters, body })})
-    }
+        self.function_yields.push(false);
+        let body = self.block_statement();
+        let is_generator = self.function_yields.pop().unwrap();
+        let body = body?;
+        Ok(Stmt::Function { ptr: Rc::new(FunctionObject {name, params: parameters, body, is_generator })})
+    }
+
+    fn var_declaration(&mut self, is_const: bool) -> Result<Stmt, String> {
+        let target = if self.match_token(&[LEFT_BRACKET]) {
+            let bracket = self.take_previous();
+            let names = self.destructure_names(RIGHT_BRACKET, "Expect ']' after destructuring pattern.")?;
+            VarTarget::List { bracket, names }
+        } else if self.match_token(&[LEFT_BRACE]) {
+            let brace = self.take_previous();
+            let names = self.destructure_names(RIGHT_BRACE, "Expect '}' after destructuring pattern.")?;
+            VarTarget::Fields { brace, names }
+        } else {
+            VarTarget::Name(self.consume(IDENTIFIER, "Expect variable name.")?)
+        };
 
-    fn var_declaration(&mut self) -> Result<Stmt, String> {
-        let name = self.consume(IDENTIFIER, "Expect variable name.")?;
         let mut initializer = Box::new(Literal { value: TokenLiteral::LOX_NULL });
         if self.match_token(&[EQUAL]) {
             initializer = self.expression()?;
+        } else if is_const {
+            // A constant could never be given a value later
+            let err_msg = String::from("Expect '=' after constant name.");
+            lox::token_error(self.peek(), &err_msg);
+            return Err(err_msg);
+        } else if !matches!(target, VarTarget::Name(_)) {
+            // There is nothing to unpack from an implicit nil
+            let err_msg = String::from("Expect '=' after destructuring pattern.");
+            lox::token_error(self.peek(), &err_msg);
+            return Err(err_msg);
         }
         self.consume(SEMICOLON, "Expect ';' after variable declaration.")?;
-        Ok(Stmt::Var { name, initializer })
+        Ok(Stmt::Var { target, initializer, is_const })
+    }
+
+    fn destructure_names(&mut self, closing: TokenType, message: &str) -> Result<Vec<Token>, String> {
+        let mut names = vec![self.consume(IDENTIFIER, "Expect variable name in destructuring pattern.")?];
+        while self.match_token(&[COMMA]) {
+            names.push(self.consume(IDENTIFIER, "Expect variable name in destructuring pattern.")?);
+        }
+        self.consume(closing, message)?;
+        Ok(names)
     }
 
     fn class_declaration(&mut self) -> Result<Stmt, String> {
@@ -101,6 +145,32 @@ impl Parser {
         Ok(Stmt::Class { name, methods, superclass })
     }
 
+    fn enum_declaration(&mut self) -> Result<Stmt, String> {
+        let name = self.consume(IDENTIFIER, "Expect enum name")?;
+        self.consume(LEFT_BRACE, "Expect '{' before enum body")?;
+
+        let mut variants = Vec::new();
+        while !self.check(RIGHT_BRACE) && !self.is_at_end() {
+            let variant = self.consume(IDENTIFIER, "Expect variant name")?;
+            let mut fields = Vec::new();
+            if self.match_token(&[LEFT_PAREN]) {
+                fields.push(self.consume(IDENTIFIER, "Expect field name")?);
+                while self.match_token(&[COMMA]) {
+                    fields.push(self.consume(IDENTIFIER, "Expect field name")?);
+                }
+                self.consume(RIGHT_PAREN, "Expect ')' after variant fields")?;
+            }
+            variants.push(EnumVariantDecl { name: variant, fields });
+
+            // Trailing comma is optional
+            if !self.match_token(&[COMMA]) {
+                break;
+            }
+        }
+        self.consume(RIGHT_BRACE, "Expect '}' after enum body")?;
+        Ok(Stmt::Enum { name, variants })
+    }
+
     fn statement(&mut self) -> Result<Stmt, String> {
         if self.match_token(&[PRINT]) {
             return self.print_statement();
@@ -127,6 +197,26 @@ impl Parser {
             return self.return_statement();
         }
 
+        if self.match_token(&[MATCH]) {
+            return self.match_statement();
+        }
+
+        if self.match_token(&[BREAK]) {
+            return self.break_statement();
+        }
+
+        if self.match_token(&[YIELD]) {
+            return self.yield_statement();
+        }
+
+        if self.match_token(&[CONTINUE]) {
+            return self.continue_statement();
+        }
+
+        if self.match_token(&[TRY]) {
+            return self.try_statement();
+        }
+
         self.expression_statement()
     }
 
@@ -170,19 +260,25 @@ impl Parser {
         let condition = self.expression()?;
         self.consume(RIGHT_PAREN, "Expect ')' after while-condition")?;
         let body = Box::new(self.statement()?);
-        Ok(Stmt::While {expression: condition, body})
+        Ok(Stmt::While {expression: condition, body, increment: None})
     }
 
     fn for_statement(&mut self) -> Result<Stmt, String> {
+        let keyword = self.take_previous();
         self.consume(LEFT_PAREN, "Expect '(' after 'for'")?;
 
+        // for (var x in collection) -- needs two tokens of lookahead past 'var'
+        if self.check(VAR) && self.peek_ahead(1).token_type == IDENTIFIER && self.peek_ahead(2).token_type == IN {
+            return self.for_in_statement(keyword);
+        }
+
         // Grab initializer
         // ; -> initializer omitted
         // var -> initializer included
         // no var -> no initialization, must be expression
         let (initializer, had_initializer) = match (self.match_token(&[SEMICOLON]), self.match_token(&[VAR])) {
             (true, _) => (Stmt::Expression { expression: Box::new(Literal { value: TokenLiteral::LOX_NULL })}, false),
-            (false, true) => (self.var_declaration()?, true),
+            (false, true) => (self.var_declaration(false)?, true),
             (false, false) => (self.expression_statement()?, true),
         };
 
@@ -195,10 +291,10 @@ impl Parser {
         self.consume(SEMICOLON, "Expect ';' after loop condition")?;
 
         // Grab increment expression
-        let (increment, had_increment) = if !self.check(RIGHT_PAREN) {
-            (self.expression()?, true)
+        let increment = if !self.check(RIGHT_PAREN) {
+            Some(self.expression()?)
         } else {
-            (Box::new(Literal { value: TokenLiteral::LOX_NULL }), false)
+            None
         };
         self.consume(RIGHT_PAREN, "Expect ')' after for clause")?;
 
@@ -211,29 +307,74 @@ impl Parser {
         }
 
         let body = self.statement()?;
-        let mut loop_body = match body {
+        let loop_body = match body {
             Stmt::Block { statements } => statements ,
             // Single statement (i.e. no braces) becomes a vector of one statement
             _ => vec![body]
         };
 
-        if had_increment {
-            // Increment is in the outer scope, loop body is in the inner scope
-            // This prevents redeclaring the looping index from affecting the loop condition
-            loop_body = vec![Stmt::Block { statements: loop_body }, Stmt::Expression { expression: increment }]
-        }
-        let outer_body = Box::new(Stmt::Block { statements: loop_body });
+        // Increment is in the outer scope, loop body is in the inner scope
+        // This prevents redeclaring the looping index from affecting the loop condition
+        let body = Box::new(Stmt::Block { statements: loop_body });
 
         if had_initializer {
-            desugared_statements.push(Stmt::While { expression: condition, body: outer_body });
+            desugared_statements.push(Stmt::While { expression: condition, body, increment });
             // Block looks like this:
-            // { Init { Condition?, Body{...}, Increment? } }
+            // { Init While(Condition?, Increment?) { Body... } }
             Ok(Stmt::Block { statements: desugared_statements })
         } else {
             // Block looks like this:
-            // { Condition?, Body {...}, Increment? }
-            Ok(Stmt::While { expression: condition, body: outer_body })
+            // While(Condition?, Increment?) { Body... }
+            Ok(Stmt::While { expression: condition, body, increment })
+        }
+    }
+
+    fn for_in_statement(&mut self, keyword: Token) -> Result<Stmt, String> {
+        self.consume(VAR, "Expect 'var' before loop variable")?;
+        let name = self.consume(IDENTIFIER, "Expect loop variable name")?;
+        self.consume(IN, "Expect 'in' after loop variable")?;
+        let iterable = self.expression()?;
+        self.consume(RIGHT_PAREN, "Expect ')' after for-in clause")?;
+        let body = Box::new(self.statement()?);
+        Ok(Stmt::ForIn { keyword, name, iterable, body })
+    }
+
+    fn yield_statement(&mut self) -> Result<Stmt, String> {
+        let keyword = self.take_previous();
+        let value = if !self.check(SEMICOLON) { self.expression()? } else {
+            Box::new(Literal { value: TokenLiteral::LOX_NULL })
+        };
+        self.consume(SEMICOLON, "Expect ';' after yield value.")?;
+
+        // Top-level yields have no function to mark, the resolver reports them instead
+        if let Some(yields) = self.function_yields.last_mut() {
+            *yields = true;
         }
+        Ok(Stmt::Yield { keyword, value })
+    }
+
+    fn break_statement(&mut self) -> Result<Stmt, String> {
+        let keyword = self.take_previous();
+        self.consume(SEMICOLON, "Expect ';' after 'break'.")?;
+        Ok(Stmt::Break { keyword })
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt, String> {
+        self.consume(LEFT_BRACE, "Expect '{' after 'try'.")?;
+        let body = Stmt::Block { statements: self.block_statement()? };
+        self.consume(CATCH, "Expect 'catch' after try block.")?;
+        self.consume(LEFT_PAREN, "Expect '(' after 'catch'.")?;
+        let name = self.consume(IDENTIFIER, "Expect error variable name.")?;
+        self.consume(RIGHT_PAREN, "Expect ')' after error variable name.")?;
+        self.consume(LEFT_BRACE, "Expect '{' before catch block.")?;
+        let handler = Stmt::Block { statements: self.block_statement()? };
+        Ok(Stmt::Try { body: Box::new(body), name, handler: Box::new(handler) })
+    }
+
+    fn continue_statement(&mut self) -> Result<Stmt, String> {
+        let keyword = self.take_previous();
+        self.consume(SEMICOLON, "Expect ';' after 'continue'.")?;
+        Ok(Stmt::Continue { keyword })
     }
 
     fn return_statement(&mut self) -> Result<Stmt, String> {
@@ -245,6 +386,92 @@ impl Parser {
         Ok(Stmt::Return { keyword, value })
     }
 
+    fn match_statement(&mut self) -> Result<Stmt, String> {
+        let keyword = self.take_previous();
+        self.consume(LEFT_PAREN, "Expect '(' after 'match'")?;
+        let subject = self.expression()?;
+        self.consume(RIGHT_PAREN, "Expect ')' after match subject")?;
+        self.consume(LEFT_BRACE, "Expect '{' before match arms")?;
+
+        let mut arms = Vec::new();
+        while !self.check(RIGHT_BRACE) && !self.is_at_end() {
+            let pattern = self.pattern()?;
+            let guard = if self.match_token(&[IF]) { Some(self.expression()?) } else { None };
+            self.consume(FAT_ARROW, "Expect '=>' after match pattern")?;
+            let body = Box::new(self.statement()?);
+            arms.push(MatchArm { pattern, guard, body });
+        }
+        self.consume(RIGHT_BRACE, "Expect '}' after match arms")?;
+        Ok(Stmt::Match { keyword, subject, arms })
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, String> {
+        if self.match_token(&[NUMBER, STRING]) {
+            return Ok(Pattern::Literal { value: self.take_previous().literal });
+        }
+
+        if self.match_token(&[MINUS]) {
+            let number = self.consume(NUMBER, "Expect number after '-' in pattern")?;
+            let value = match number.literal {
+                TokenLiteral::LOX_NUMBER(value) => TokenLiteral::LOX_NUMBER(-value),
+                // Literals never exceed i64::MAX, so negating them can't overflow
+                TokenLiteral::LOX_INT(value) => TokenLiteral::LOX_INT(-value),
+                TokenLiteral::LOX_BIGINT(value) => value.neg().into_literal(),
+                _ => unreachable!("Number tokens always carry a number literal")
+            };
+            return Ok(Pattern::Literal { value });
+        }
+
+        if self.match_token(&[TRUE]) {
+            return Ok(Pattern::Literal { value: TokenLiteral::LOX_BOOL(true) });
+        }
+
+        if self.match_token(&[FALSE]) {
+            return Ok(Pattern::Literal { value: TokenLiteral::LOX_BOOL(false) });
+        }
+
+        if self.match_token(&[NIL]) {
+            return Ok(Pattern::Literal { value: TokenLiteral::LOX_NULL });
+        }
+
+        if self.match_token(&[IDENTIFIER]) {
+            let name = self.take_previous();
+
+            // `Enum.Variant` or `Enum.Variant(a, b)` tests the variant before binding its values
+            if self.match_token(&[DOT]) {
+                let variant = self.consume(IDENTIFIER, "Expect variant name after '.' in pattern")?;
+                let mut bindings = Vec::new();
+                if self.match_token(&[LEFT_PAREN]) {
+                    bindings.push(self.consume(IDENTIFIER, "Expect binding name in variant pattern")?);
+                    while self.match_token(&[COMMA]) {
+                        bindings.push(self.consume(IDENTIFIER, "Expect binding name in variant pattern")?);
+                    }
+                    self.consume(RIGHT_PAREN, "Expect ')' after variant pattern")?;
+                }
+                let id = self.curr_id;
+                self.curr_id += 1;
+                return Ok(Pattern::Enum { enum_type: Box::new(Variable { name, id }), variant, bindings });
+            }
+
+            // `ClassName binding` tests the subject's class before binding it
+            if self.match_token(&[IDENTIFIER]) {
+                let binding = self.take_previous();
+                let id = self.curr_id;
+                self.curr_id += 1;
+                return Ok(Pattern::Class { class: Box::new(Variable { name, id }), binding });
+            }
+
+            return match name.lexeme.as_str() {
+                "_" => Ok(Pattern::Wildcard),
+                _ => Ok(Pattern::Binding { name }),
+            };
+        }
+
+        let err_msg = String::from("Expect match pattern.");
+        lox::token_error(self.peek(), &err_msg);
+        Err(err_msg)
+    }
+
     fn expression(&mut self) -> Result<Box<Expr>, String> {
         self.assignment()
     }
@@ -267,6 +494,16 @@ impl Parser {
                     self.curr_id += 1;
                     Ok(Box::new(Set { object, name, value, id }))
                 }
+                Index { object, bracket, index } => {
+                    Ok(Box::new(SetIndex { object, bracket, index, value }))
+                }
+                // Swap-style assignment: [a, b] = [b, a];
+                List { bracket, elements } => {
+                    if elements.iter().any(|target| !matches!(target, Variable { .. } | Get { .. } | Index { .. })) {
+                        lox::token_error(&bracket, "Invalid destructuring assignment target.");
+                    }
+                    Ok(Box::new(Unpack { bracket, targets: elements, value }))
+                }
                 _ => {
                     // Error if left-hand-side is an invalid assignment target
                     // Report error but do not throw it
@@ -339,9 +576,15 @@ impl Parser {
         &self.tokens[self.current as usize]
     }
 
+    fn peek_ahead(&self, distance: usize) -> &Token {
+        // Never look past EOF
+        let index = (self.current as usize + distance).min(self.tokens.len() - 1);
+        &self.tokens[index]
+    }
+
     fn take_previous(&mut self) -> Token {
         let dest = &mut self.tokens[(self.current - 1) as usize];
-        mem::replace(dest, Token::new(NIL, String::new(), TokenLiteral::LOX_NULL, -1))
+        mem::replace(dest, Token::new(NIL, String::new(), TokenLiteral::LOX_NULL, -1, 0))
     }
 
     fn comparison(&mut self) -> Result<Box<Expr>, String> {
@@ -395,6 +638,12 @@ impl Parser {
                 self.curr_id += 1;
                 expr = Box::new(Get { object: expr, name, id });
             }
+            else if self.match_token(&[LEFT_BRACKET]) {
+                let bracket = self.take_previous();
+                let index = self.expression()?;
+                self.consume(RIGHT_BRACKET, "Expect ']' after index.")?;
+                expr = Box::new(Index { object: expr, bracket, index });
+            }
             else {
                 break;
             }
@@ -464,6 +713,35 @@ impl Parser {
             return Ok(Box::new(Grouping { expression: expr }));
         }
 
+        if self.match_token(&[LEFT_BRACKET]) {
+            let bracket = self.take_previous();
+            let mut elements = Vec::new();
+            if !self.check(RIGHT_BRACKET) {
+                elements.push(*self.expression()?);
+                while self.match_token(&[COMMA]) {
+                    elements.push(*self.expression()?);
+                }
+            }
+            self.consume(RIGHT_BRACKET, "Expect ']' after list elements.")?;
+            return Ok(Box::new(List { bracket, elements }));
+        }
+
+        if self.match_token(&[LEFT_BRACE]) {
+            let brace = self.take_previous();
+            let mut entries = Vec::new();
+            if !self.check(RIGHT_BRACE) {
+                loop {
+                    let key = *self.expression()?;
+                    self.consume(COLON, "Expect ':' after map key.")?;
+                    let value = *self.expression()?;
+                    entries.push((key, value));
+                    if !self.match_token(&[COMMA]) { break; }
+                }
+            }
+            self.consume(RIGHT_BRACE, "Expect '}' after map entries.")?;
+            return Ok(Box::new(Map { brace, entries }));
+        }
+
         let err_msg = String::from("Expected expression");
         lox::token_error(self.peek(), &err_msg);
         Err(err_msg)
@@ -484,7 +762,7 @@ impl Parser {
         while !self.is_at_end() {
             if self.take_previous().token_type == SEMICOLON { return; }
             match self.peek().token_type {
-                CLASS | FUN | VAR | FOR | IF | WHILE | PRINT | RETURN => {
+                BREAK | CLASS | CONST | CONTINUE | ENUM | FUN | VAR | FOR | IF | MATCH | WHILE | PRINT | RETURN | TRY | YIELD => {
                     return;
                 }
                 _ => (),
@@ -0,0 +1,40 @@
+use crate::expression::Expr;
+use crate::statement::Stmt;
+use crate::token::Token;
+use crate::token_literal::TokenLiteral;
+
+pub enum Pattern {
+    // 1, "str", true, nil
+    Literal { value: TokenLiteral },
+    // _
+    Wildcard,
+    // name -- matches anything and binds it
+    Binding { name: Token },
+    // Point p -- matches instances of the class (or a subclass) and binds them
+    Class { class: Box<Expr>, binding: Token },
+    // Color.Red, Shape.Circle(r) -- matches one variant of an enum and binds its associated values
+    Enum { enum_type: Box<Expr>, variant: Token, bindings: Vec<Token> },
+}
+
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Box<Expr>>,
+    pub body: Box<Stmt>,
+}
+
+impl Pattern {
+    /// Names bound by this pattern, in the order they are declared in the arm's scope
+    pub fn bindings(&self) -> Vec<&Token> {
+        match self {
+            Pattern::Binding { name } => vec![name],
+            Pattern::Class { binding, .. } if binding.lexeme != "_" => vec![binding],
+            Pattern::Enum { bindings, .. } => bindings.iter().filter(|binding| binding.lexeme != "_").collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Whether the pattern matches every possible value
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Wildcard | Pattern::Binding { .. })
+    }
+}
@@ -0,0 +1,55 @@
+use std::env;
+use std::rc::Rc;
+
+use crate::interpreter::InterpreterError;
+use crate::token_literal::TokenLiteral::{self, LOX_INT, LOX_NULL, LOX_STRING};
+
+// Natives for talking to the process running the script
+// The script's own arguments come from the host, which registers `args` separately
+#[derive(Clone, Copy)]
+pub enum ProcessNative {
+    GetEnv,
+    Exit,
+    EPrint,
+}
+
+impl ProcessNative {
+    pub const ALL: [ProcessNative; 3] = [ProcessNative::GetEnv, ProcessNative::Exit, ProcessNative::EPrint];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcessNative::GetEnv => "getenv",
+            ProcessNative::Exit => "exit",
+            ProcessNative::EPrint => "eprint",
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        1
+    }
+
+    pub fn call(&self, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match self {
+            // nil for unset variables
+            ProcessNative::GetEnv => match &arguments[0] {
+                LOX_STRING(name) => match env::var(name.as_str()) {
+                    Ok(value) => Ok(LOX_STRING(Rc::new(value))),
+                    Err(_) => Ok(LOX_NULL),
+                },
+                other => Err(InterpreterError::NativeError(format!("getenv() expects a variable name but got {other}."))),
+            },
+            // Unwinds like an error that nothing catches, so the host decides how to end the process
+            ProcessNative::Exit => match &arguments[0] {
+                LOX_INT(code) => match i32::try_from(*code) {
+                    Ok(code) => Err(InterpreterError::Exit(code)),
+                    Err(_) => Err(InterpreterError::NativeError(format!("exit() code {code} is out of range."))),
+                },
+                other => Err(InterpreterError::NativeError(format!("exit() expects an integer code but got {other}."))),
+            },
+            ProcessNative::EPrint => {
+                eprintln!("{}", arguments[0]);
+                Ok(LOX_NULL)
+            }
+        }
+    }
+}
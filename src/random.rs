@@ -0,0 +1,122 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::token_literal::TokenLiteral::{self, LOX_INT, LOX_LIST, LOX_NULL, LOX_NUMBER};
+
+// xorshift64* generator, held by the interpreter so that seeding it makes a whole run reproducible
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Seeds from the clock, for runs that never call `seed`
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        Rng::from_seed(nanos as u64)
+    }
+
+    pub fn from_seed(seed: u64) -> Self {
+        // splitmix64 scrambles the seed, so that nearby seeds give unrelated sequences
+        // and a seed of zero doesn't leave xorshift stuck at zero forever
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Self { state: if z == 0 { 1 } else { z } }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in [0, 1), using the top 53 bits so every value is exactly representable
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in [0, bound), rejecting the values that would bias the result towards small numbers
+    pub fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum RandomNative {
+    Random,
+    RandomInt,
+    Shuffle,
+    Seed,
+}
+
+impl RandomNative {
+    pub const ALL: [RandomNative; 4] = [RandomNative::Random, RandomNative::RandomInt, RandomNative::Shuffle, RandomNative::Seed];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RandomNative::Random => "random",
+            RandomNative::RandomInt => "randomInt",
+            RandomNative::Shuffle => "shuffle",
+            RandomNative::Seed => "seed",
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            RandomNative::Random => 0,
+            RandomNative::Shuffle | RandomNative::Seed => 1,
+            RandomNative::RandomInt => 2,
+        }
+    }
+
+    pub fn call(&self, interpreter: &mut Interpreter, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match self {
+            RandomNative::Random => Ok(LOX_NUMBER(interpreter.rng.next_f64())),
+            // Both bounds are inclusive
+            RandomNative::RandomInt => match (&arguments[0], &arguments[1]) {
+                (LOX_INT(low), LOX_INT(high)) if low <= high => {
+                    let span = high.abs_diff(*low);
+                    let offset = match span.checked_add(1) {
+                        Some(bound) => interpreter.rng.below(bound),
+                        // The full i64 range: every u64 is a valid offset
+                        None => interpreter.rng.next_u64(),
+                    };
+                    Ok(LOX_INT(low.wrapping_add_unsigned(offset)))
+                }
+                (LOX_INT(low), LOX_INT(high)) => Err(self.error(format!("low bound {low} is greater than high bound {high}."))),
+                _ => Err(self.error(String::from("expects two integers."))),
+            },
+            // Fisher-Yates, in place; the list is returned to allow chaining
+            RandomNative::Shuffle => match &arguments[0] {
+                LOX_LIST(list) => {
+                    let mut items = list.borrow_mut();
+                    for i in (1..items.len()).rev() {
+                        let j = interpreter.rng.below(i as u64 + 1) as usize;
+                        items.swap(i, j);
+                    }
+                    Ok(arguments[0].clone())
+                }
+                other => Err(self.error(format!("expects a list but got {other}."))),
+            },
+            RandomNative::Seed => match &arguments[0] {
+                LOX_INT(seed) => {
+                    interpreter.rng = Rng::from_seed(*seed as u64);
+                    Ok(LOX_NULL)
+                }
+                other => Err(self.error(format!("expects an integer but got {other}."))),
+            },
+        }
+    }
+
+    fn error(&self, message: String) -> InterpreterError {
+        InterpreterError::NativeError(format!("{}() {message}", self.name()))
+    }
+}
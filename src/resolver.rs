@@ -1,10 +1,12 @@
 use std::collections::HashMap;
+use std::mem;
 use std::ops::Deref;
 use std::rc::Rc;
 use crate::expression::Expr;
 use crate::function_object::FunctionObject;
 use crate::interpreter::Interpreter;
 use crate::lox;
+use crate::pattern::{MatchArm, Pattern};
 use crate::statement::Stmt;
 use crate::token::Token;
 use crate::token_literal::TokenLiteral;
@@ -12,12 +14,71 @@ use crate::token_literal::TokenLiteral;
 // Resolver traverses all AST nodes in a single pass
 pub struct Resolver <'a> {
     interpreter: &'a mut Interpreter,
-    scopes: Vec<HashMap<String, bool>>,
+    scopes: Vec<HashMap<String, ScopeEntry>>,
     current_function: FunctionType,
     current_class: ClassType,
+    // Number of loops enclosing the current statement within the current function
+    loop_depth: usize,
+    in_generator: bool,
+    // Variant names of every enum declared so far, used to check that a match covers all of them
+    enums: HashMap<String, Vec<String>>,
+    // Every declaration and resolved use of a name, for editor tooling, see `symbols`
+    symbols: Vec<Symbol>,
+    // Where each global was first declared, as uses of globals may come before their declaration
+    globals: HashMap<String, (i32, i32)>,
+    // Every local binding whose scope has ended, for lints, see `bindings`
+    bindings: Vec<Binding>,
+}
+
+/// A name in the source, at the line and column of its token, along with the line and column of the declaration it refers to
+/// Declarations refer to themselves; uses of natives and undeclared globals have no declaration
+pub struct Symbol {
+    pub name: String,
+    pub line: i32,
+    pub column: i32,
+    pub declaration: Option<(i32, i32)>,
+}
+
+/// A local binding once its scope has ended, with how the code in that scope used it
+/// Globals aren't tracked, as they may be used by code the resolver never sees
+pub struct Binding {
+    pub name: String,
+    pub line: i32,
+    pub column: i32,
+    pub kind: BindingKind,
+    // Whether any expression reads the binding; assigning to it doesn't count
+    pub read: bool,
+    // Whether an enclosing local scope already had a binding of the same name
+    pub shadows: bool,
 }
 
-#[allow(non_camel_case_types)]
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub enum BindingKind {
+    Variable,
+    Function,
+    Parameter,
+    // Looked up on instances rather than by name, so never read or shadowed through the scope
+    Method,
+}
+
+#[derive(Copy, Clone)]
+struct ScopeEntry {
+    // `false` until the variable's initializer has been resolved
+    defined: bool,
+    // `false` for bindings declared with `const`
+    mutable: bool,
+    // Line and column of the declaring token, None for the implicit `this` and `super`
+    declaration: Option<(i32, i32)>,
+    kind: BindingKind,
+    read: bool,
+    shadows: bool,
+}
+
+impl ScopeEntry {
+    const DEFINED: ScopeEntry = ScopeEntry { defined: true, mutable: true, declaration: None, kind: BindingKind::Variable, read: true, shadows: false };
+}
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 #[derive(Eq, PartialEq, Copy, Clone)]
 enum FunctionType {
     NO_FUNCTION,
@@ -26,7 +87,7 @@ enum FunctionType {
     METHOD,
 }
 
-#[allow(non_camel_case_types)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 #[derive(Eq, PartialEq, Copy, Clone)]
 enum ClassType {
     NO_CLASS,
@@ -36,20 +97,45 @@ enum ClassType {
 
 impl <'a> Resolver <'a> {
     pub fn new (interpreter: &'a mut Interpreter) -> Self {
-        Self { interpreter, scopes: Vec::new(), current_function: FunctionType::NO_FUNCTION, current_class: ClassType::NO_CLASS }
+        Self { interpreter, scopes: Vec::new(), current_function: FunctionType::NO_FUNCTION, current_class: ClassType::NO_CLASS, loop_depth: 0, in_generator: false, enums: HashMap::new(), symbols: Vec::new(), globals: HashMap::new(), bindings: Vec::new() }
+    }
+
+    /// The local bindings of the statements resolved so far, in no particular order
+    pub fn bindings(self) -> Vec<Binding> {
+        self.bindings
+    }
+
+    /// The symbols found by the statements resolved so far, in the order they were resolved
+    pub fn symbols(self) -> Vec<Symbol> {
+        let globals = self.globals;
+        self.symbols.into_iter().map(|symbol| match symbol.declaration {
+            Some(_) => symbol,
+            None => Symbol { declaration: globals.get(&symbol.name).copied(), ..symbol },
+        }).collect()
+    }
+
+    fn add_symbol(&mut self, name: &Token, declaration: Option<(i32, i32)>) {
+        self.symbols.push(Symbol { name: name.lexeme.clone(), line: name.line, column: name.column, declaration });
     }
 
     pub fn resolve_stmt(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Block { .. } => self.resolve_block_stmt(stmt),
+            Stmt::Break { .. } => self.resolve_loop_jump_stmt(stmt),
             Stmt::Class { .. } => self.resolve_class_stmt(stmt),
+            Stmt::Continue { .. } => self.resolve_loop_jump_stmt(stmt),
+            Stmt::Enum { .. } => self.resolve_enum_stmt(stmt),
             Stmt::Expression { .. } => self.resolve_expression_stmt(stmt),
+            Stmt::ForIn { .. } => self.resolve_for_in_stmt(stmt),
             Stmt::Function { .. } => self.resolve_function_stmt(stmt, FunctionType::FUNCTION),
             Stmt::If { .. } => self.resolve_if_stmt(stmt),
+            Stmt::Match { .. } => self.resolve_match_stmt(stmt),
             Stmt::Print { .. } => self.resolve_print_stmt(stmt),
             Stmt::Return { .. } => self.resolve_return_stmt(stmt),
+            Stmt::Try { .. } => self.resolve_try_stmt(stmt),
             Stmt::Var { .. } => self.resolve_var_stmt(stmt),
             Stmt::While { .. } => self.resolve_while_stmt(stmt),
+            Stmt::Yield { .. } => self.resolve_yield_stmt(stmt),
         }
     }
 
@@ -60,12 +146,17 @@ impl <'a> Resolver <'a> {
             Expr::Call { .. } => self.resolve_call_expr(expr),
             Expr::Get { .. } => self.resolve_get_expr(expr),
             Expr::Grouping { .. } => self.resolve_grouping_expr(expr),
+            Expr::Index { .. } => self.resolve_index_expr(expr),
+            Expr::List { .. } => self.resolve_list_expr(expr),
             Expr::Literal { .. } => self.resolve_literal_expr(expr),
             Expr::Logical { .. } => self.resolve_logical_expr(expr),
+            Expr::Map { .. } => self.resolve_map_expr(expr),
             Expr::Set { .. } => self.resolve_set_expr(expr),
+            Expr::SetIndex { .. } => self.resolve_set_index_expr(expr),
             Expr::Super { .. } => self.resolve_super_expr(expr),
             Expr::This { .. } => self.resolve_this_expr(expr),
             Expr::Unary { .. } => self.resolve_unary_expr(expr),
+            Expr::Unpack { .. } => self.resolve_unpack_expr(expr),
             Expr::Variable { .. } => self.resolve_var_expr(expr)
         }
     }
@@ -75,28 +166,67 @@ impl <'a> Resolver <'a> {
     }
 
     fn end_scope(&mut self) {
-        self.scopes.pop();
+        let scope = self.scopes.pop().unwrap();
+        for (name, entry) in scope {
+            if let Some((line, column)) = entry.declaration {
+                self.bindings.push(Binding { name, line, column, kind: entry.kind, read: entry.read, shadows: entry.shadows });
+            }
+        }
     }
 
-    fn declare_var(&mut self, name: &Token) {
-        if !self.scopes.is_empty() {
+    fn declare_var(&mut self, name: &Token, kind: BindingKind) {
+        self.declare(name, true, kind);
+    }
 
-            let scope = self.scopes.last_mut().unwrap();
+    fn declare_const(&mut self, name: &Token) {
+        self.declare(name, false, BindingKind::Variable);
+    }
+
+    fn declare(&mut self, name: &Token, mutable: bool, kind: BindingKind) {
+        let declaration = (name.line, name.column);
+        self.add_symbol(name, Some(declaration));
+        if self.scopes.is_empty() {
+            self.globals.entry(name.lexeme.clone()).or_insert(declaration);
+        } else {
+            let (scope, outer_scopes) = self.scopes.split_last_mut().unwrap();
 
             if scope.contains_key(&name.lexeme) {
                 lox::token_error(name, "Already a variable with this name in this scope.")
             }
 
+            if outer_scopes.iter().any(|outer| outer.get(&name.lexeme).is_some_and(|entry| !entry.mutable)) {
+                lox::token_warning(name, "Declaration shadows a constant from an enclosing scope.");
+            }
+            let shadows = kind != BindingKind::Method && outer_scopes.iter().any(|outer| {
+                outer.get(&name.lexeme).is_some_and(|entry| entry.declaration.is_some() && entry.kind != BindingKind::Method)
+            });
+
             // Add to innermost scope to shadow any outer ones
-            // Mark "not finished resolving the variable's initializer" with `false`
-            scope.insert(name.lexeme.clone(), false);
+            // Mark "not finished resolving the variable's initializer" with `defined: false`
+            let entry = ScopeEntry { defined: false, mutable, declaration: Some(declaration), kind, read: false, shadows };
+            scope.insert(name.lexeme.clone(), entry);
         }
     }
 
     fn define_var(&mut self, name: &Token) {
         if !self.scopes.is_empty() {
             // Should not fail if define is always called after declare
-            *self.scopes.last_mut().unwrap().get_mut(&name.lexeme).unwrap() = true;
+            self.scopes.last_mut().unwrap().get_mut(&name.lexeme).unwrap().defined = true;
+        }
+    }
+
+    fn mark_read(&mut self, name: &Token) {
+        if let Some(entry) = self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(&name.lexeme)) {
+            entry.read = true;
+        }
+    }
+
+    fn check_mutable(&self, name: &Token) {
+        // Only the innermost binding of a name can be the target of an assignment
+        // Globals are not tracked here; the global environment rejects them at runtime instead
+        let entry = self.scopes.iter().rev().find_map(|scope| scope.get(&name.lexeme));
+        if entry.is_some_and(|entry| !entry.mutable) {
+            lox::token_error(name, "Can't assign to a constant.");
         }
     }
 
@@ -123,7 +253,7 @@ impl <'a> Resolver <'a> {
                 let enclosing_class = self.current_class;
 
                 self.current_class = ClassType::CLASS;
-                self.declare_var(name);
+                self.declare_var(name, BindingKind::Variable);
                 self.define_var(name);
 
                 if superclass.is_some() {
@@ -137,12 +267,12 @@ impl <'a> Resolver <'a> {
                     self.resolve_expr(superclass);
 
                     self.begin_scope();
-                    self.scopes.last_mut().unwrap().insert(String::from("super"), true);
+                    self.scopes.last_mut().unwrap().insert(String::from("super"), ScopeEntry::DEFINED);
                 }
 
                 self.begin_scope();
                 // Resolve a 'this' to the local variable in the current method scope
-                self.scopes.last_mut().unwrap().insert(String::from("this"), true);
+                self.scopes.last_mut().unwrap().insert(String::from("this"), ScopeEntry::DEFINED);
 
                 for method in methods.iter() {
                     let declaration = match method {
@@ -168,10 +298,18 @@ impl <'a> Resolver <'a> {
 
     fn resolve_var_stmt(&mut self, stmt: &Stmt) {
         match stmt {
-            Stmt::Var { name, initializer } => {
-                self.declare_var(name);
+            Stmt::Var { target, initializer, is_const } => {
+                let names = target.names();
+                for name in names.iter() {
+                    match is_const {
+                        true => self.declare_const(name),
+                        false => self.declare_var(name, BindingKind::Variable),
+                    }
+                }
                 self.resolve_expr(initializer);
-                self.define_var(name);
+                for name in names.iter() {
+                    self.define_var(name);
+                }
             }
             _ => unreachable!("Non-variable statement passed to variable resolver visitor")
         }
@@ -181,7 +319,8 @@ impl <'a> Resolver <'a> {
         match stmt {
             Stmt::Function { ptr } => {
                 let name = &ptr.as_ref().name;
-                self.declare_var(name);
+                let kind = if function_type == FunctionType::FUNCTION { BindingKind::Function } else { BindingKind::Method };
+                self.declare_var(name, kind);
                 self.define_var(name);
                 self.resolve_function(ptr, function_type)
             }
@@ -192,16 +331,21 @@ impl <'a> Resolver <'a> {
     fn resolve_function(&mut self, function: &Rc<FunctionObject>, function_type: FunctionType) {
         let enclosing_function_type = self.current_function;
         self.current_function = function_type;
+        // Loops outside of the function can't be broken out of from inside it
+        let enclosing_loop_depth = mem::take(&mut self.loop_depth);
+        let enclosing_generator = mem::replace(&mut self.in_generator, function.is_generator);
 
         self.begin_scope();
         for param in function.params.iter() {
-            self.declare_var(param);
+            self.declare_var(param, BindingKind::Parameter);
             self.define_var(param);
         }
         self.resolve_statements(&function.body);
         self.end_scope();
 
         self.current_function = enclosing_function_type;
+        self.loop_depth = enclosing_loop_depth;
+        self.in_generator = enclosing_generator;
     }
 
     fn resolve_expression_stmt(&mut self, stmt: &Stmt) {
@@ -222,6 +366,78 @@ impl <'a> Resolver <'a> {
         }
     }
 
+    fn resolve_match_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Match { keyword, subject, arms } => {
+                self.resolve_expr(subject);
+                for arm in arms.iter() {
+                    // The class is looked up outside of the arm's scope
+                    match &arm.pattern {
+                        Pattern::Class { class, .. } => self.resolve_expr(class),
+                        Pattern::Enum { enum_type, .. } => self.resolve_expr(enum_type),
+                        _ => (),
+                    }
+
+                    // Every arm gets its own scope for the names its pattern binds
+                    self.begin_scope();
+                    for name in arm.pattern.bindings() {
+                        self.declare_var(name, BindingKind::Variable);
+                        self.define_var(name);
+                    }
+                    if let Some(guard) = &arm.guard {
+                        self.resolve_expr(guard);
+                    }
+                    self.resolve_stmt(&arm.body);
+                    self.end_scope();
+                }
+
+                if !arms.iter().any(|arm| arm.guard.is_none() && arm.pattern.is_irrefutable()) {
+                    match self.missing_variants(arms) {
+                        Some(missing) if missing.is_empty() => (),
+                        Some(missing) => {
+                            let message = format!("Match does not cover variants {}; unmatched values are ignored.", missing.join(", "));
+                            lox::token_warning(keyword, &message);
+                        }
+                        None => lox::token_warning(keyword, "Match has no default arm; unmatched values are ignored."),
+                    }
+                }
+            }
+            _ => unreachable!("Non-match statement passed to match resolver visitor")
+        }
+    }
+
+    /// Variants of the matched enum that no unguarded arm covers, if every arm matches variants of one known enum
+    fn missing_variants(&self, arms: &[MatchArm]) -> Option<Vec<String>> {
+        let mut enum_name: Option<&str> = None;
+        let mut covered = Vec::new();
+        for arm in arms.iter() {
+            let Pattern::Enum { enum_type: expr, variant, .. } = &arm.pattern else { return None };
+            let Expr::Variable { name, .. } = expr.as_ref() else { return None };
+            if enum_name.is_some_and(|enum_name| enum_name != name.lexeme) {
+                return None;
+            }
+            enum_name = Some(&name.lexeme);
+            if arm.guard.is_none() {
+                covered.push(&variant.lexeme);
+            }
+        }
+
+        let variants = self.enums.get(enum_name?)?;
+        Some(variants.iter().filter(|variant| !covered.contains(variant)).cloned().collect())
+    }
+
+    fn resolve_enum_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Enum { name, variants } => {
+                self.declare_var(name, BindingKind::Variable);
+                self.define_var(name);
+                let variant_names = variants.iter().map(|variant| variant.name.lexeme.clone()).collect();
+                self.enums.insert(name.lexeme.clone(), variant_names);
+            }
+            _ => unreachable!("Non-enum statement passed to enum resolver visitor")
+        }
+    }
+
     fn resolve_print_stmt(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Print { expression} => self.resolve_expr(expression),
@@ -242,6 +458,9 @@ impl <'a> Resolver <'a> {
                         if self.current_function == FunctionType::INITIALIZER {
                             lox::token_error(keyword, "Can't return a value from an initializer");
                         }
+                        if self.in_generator {
+                            lox::token_error(keyword, "Can't return a value from a generator.");
+                        }
                     }
                 };
 
@@ -253,23 +472,93 @@ impl <'a> Resolver <'a> {
 
     fn resolve_while_stmt(&mut self, stmt: &Stmt) {
         match stmt {
-            Stmt::While { expression, body } => {
+            Stmt::While { expression, body, increment } => {
                 self.resolve_expr(expression);
+                self.loop_depth += 1;
                 self.resolve_stmt(body);
+                self.loop_depth -= 1;
+                if let Some(increment) = increment {
+                    self.resolve_expr(increment);
+                }
             }
             _ => unreachable!("Non-while statement passed to while resolver visitor")
         }
     }
 
+    fn resolve_yield_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Yield { keyword, value } => {
+                match self.current_function {
+                    FunctionType::NO_FUNCTION => lox::token_error(keyword, "Can't yield from top-level code."),
+                    FunctionType::INITIALIZER => lox::token_error(keyword, "Can't yield from an initializer."),
+                    _ => ()
+                }
+                self.resolve_expr(value);
+            }
+            _ => unreachable!("Non-yield statement passed to yield resolver visitor")
+        }
+    }
+
+    fn resolve_for_in_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::ForIn { name, iterable, body, .. } => {
+                self.resolve_expr(iterable);
+
+                // The loop variable lives in its own scope, fresh for every iteration
+                self.begin_scope();
+                self.declare_var(name, BindingKind::Variable);
+                self.define_var(name);
+                self.loop_depth += 1;
+                self.resolve_stmt(body);
+                self.loop_depth -= 1;
+                self.end_scope();
+            }
+            _ => unreachable!("Non-for-in statement passed to for-in resolver visitor")
+        }
+    }
+
+    fn resolve_try_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Try { body, name, handler } => {
+                self.resolve_stmt(body);
+                // The error variable lives in a scope wrapping the catch block
+                self.begin_scope();
+                self.declare_var(name, BindingKind::Variable);
+                self.define_var(name);
+                // The syntax requires a name even when the error isn't looked at
+                self.mark_read(name);
+                self.resolve_stmt(handler);
+                self.end_scope();
+            }
+            _ => unreachable!("Non-try statement passed to try resolver visitor")
+        }
+    }
+
+    fn resolve_loop_jump_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Break { keyword } | Stmt::Continue { keyword } => {
+                if self.loop_depth == 0 {
+                    lox::token_error(keyword, &format!("Can't use '{}' outside of a loop.", keyword.lexeme));
+                }
+            }
+            _ => unreachable!("Non-break/continue statement passed to loop jump resolver visitor")
+        }
+    }
+
 
     fn resolve_local_var(&mut self, expr: &Expr, variable: &Token) {
         // Search from innermost scope outwards to determine the number of scopes
         for (i, scope) in self.scopes.iter().enumerate().rev() {
-            if scope.contains_key(&variable.lexeme) {
+            if let Some(entry) = scope.get(&variable.lexeme) {
+                if let Some(declaration) = entry.declaration {
+                    self.add_symbol(variable, Some(declaration));
+                }
                 self.interpreter.resolve(expr, self.scopes.len() - 1 - i);
                 return;
             }
         }
+        // Not found, so it's looked up as a global at runtime
+        self.add_symbol(variable, None);
     }
 
     fn resolve_var_expr(&mut self, expr: &Expr) {
@@ -281,19 +570,21 @@ impl <'a> Resolver <'a> {
         // Values in scopes map indicate whether a variable has been defined
         if !self.scopes.is_empty() {
             let last_scope = self.scopes.last().unwrap();
-            if last_scope.contains_key(&variable.lexeme) && !*last_scope.get(&variable.lexeme).unwrap() {
+            if last_scope.contains_key(&variable.lexeme) && !last_scope.get(&variable.lexeme).unwrap().defined {
                 // Variable exists in current scope but is undefined (set to `false`)
                 lox::token_error(variable, "Can't read local variable in its own initializer.")
             }
         }
 
         self.resolve_local_var(expr, variable);
+        self.mark_read(variable);
     }
 
     fn resolve_assign_expr(&mut self, expr: &Expr) {
         match expr {
             Expr::Assign { name, value , .. } => {
                 self.resolve_expr(value);
+                self.check_mutable(name);
                 self.resolve_local_var(expr, name);
             }
             _ => unreachable!("Non-assign expression passed to assign resolver visitor")
@@ -336,6 +627,27 @@ impl <'a> Resolver <'a> {
         }
     }
 
+    fn resolve_index_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Index { object, index, .. } => {
+                self.resolve_expr(object);
+                self.resolve_expr(index);
+            }
+            _ => unreachable!("Non-index expression passed to index resolver visitor")
+        }
+    }
+
+    fn resolve_list_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::List { elements, .. } => {
+                for element in elements.iter() {
+                    self.resolve_expr(element);
+                }
+            }
+            _ => unreachable!("Non-list expression passed to list resolver visitor")
+        }
+    }
+
     fn resolve_literal_expr(&mut self, expr: &Expr) {
         match expr {
             // Literals contain no variables or sub-expressions to resolve
@@ -354,6 +666,18 @@ impl <'a> Resolver <'a> {
         }
     }
 
+    fn resolve_map_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Map { entries, .. } => {
+                for (key, value) in entries.iter() {
+                    self.resolve_expr(key);
+                    self.resolve_expr(value);
+                }
+            }
+            _ => unreachable!("Non-map expression passed to map resolver visitor")
+        }
+    }
+
     fn resolve_set_expr(&mut self, expr: &Expr) {
         match expr {
             Expr::Set { object, value, .. } => {
@@ -364,6 +688,17 @@ impl <'a> Resolver <'a> {
         }
     }
 
+    fn resolve_set_index_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::SetIndex { object, index, value, .. } => {
+                self.resolve_expr(object);
+                self.resolve_expr(index);
+                self.resolve_expr(value);
+            }
+            _ => unreachable!("Non-set-index expression passed to set-index resolver visitor")
+        }
+    }
+
     fn resolve_super_expr(&mut self, expr: &Expr) {
         match expr {
             Expr::Super { keyword, .. } => {
@@ -402,4 +737,28 @@ impl <'a> Resolver <'a> {
         }
     }
 
+    fn resolve_unpack_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Unpack { targets, value, .. } => {
+                self.resolve_expr(value);
+                for target in targets.iter() {
+                    match target {
+                        // Each variable target is resolved like the name of a regular assignment
+                        Expr::Variable { name, .. } => {
+                            self.check_mutable(name);
+                            self.resolve_local_var(target, name);
+                        }
+                        Expr::Get { object, .. } => self.resolve_expr(object),
+                        Expr::Index { object, index, .. } => {
+                            self.resolve_expr(object);
+                            self.resolve_expr(index);
+                        }
+                        _ => ()
+                    }
+                }
+            }
+            _ => unreachable!("Non-unpack expression passed to unpack resolver visitor")
+        }
+    }
+
 }
\ No newline at end of file
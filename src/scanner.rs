@@ -2,6 +2,7 @@ use std::mem;
 use std::str::{self, FromStr};
 use std::rc::Rc;
 
+use crate::bigint::BigInt;
 use crate::lox;
 use crate::token::Token;
 use crate::token_literal::TokenLiteral::{self, *};
@@ -13,6 +14,14 @@ pub struct Scanner {
     start: i32,
     current: i32,
     line: i32,
+    // Line where the current token starts, as multi-line strings end on a later line than they start
+    start_line: i32,
+    // Column in characters of the current token, and of the byte at `current`,
+    // kept up to date as the scanner moves so that no line has to be rescanned
+    column: i32,
+    current_column: i32,
+    // Comments are dropped unless a tool like the formatter needs them
+    keep_comments: bool,
 }
 
 impl Scanner {
@@ -22,31 +31,44 @@ impl Scanner {
             tokens: vec![],
             start: 0,
             current: 0,
-            line: 1
+            line: 1,
+            start_line: 1,
+            column: 1,
+            current_column: 1,
+            keep_comments: false,
         }
     }
 
+    /// A scanner that also emits `COMMENT` tokens, which the parser doesn't accept
+    pub fn with_comments(source: String) -> Self {
+        Scanner { keep_comments: true, ..Scanner::new(source) }
+    }
+
     pub fn scan_tokens(&mut self) -> Vec<Token> {
         while !self.is_at_end() {
             self.start = self.current;
+            self.start_line = self.line;
+            self.column = self.current_column;
             self.scan_token();
         }
-        self.tokens.push(Token::new(EOF, String::from(""), LOX_NULL, self.line));
+        self.tokens.push(Token::new(EOF, String::from(""), LOX_NULL, self.line, self.current_column));
         mem::take(&mut self.tokens)
     }
 
     /// None gets the current char
     /// Otherwise, use the passed index
+    /// Past the end of the source, e.g. when the last token runs up to it, this is `\0`
     fn get_source_char(&self, index: Option<usize>) -> u8 {
-        match index {
-            None => self.source.as_bytes()[self.current as usize],
-            Some(i) => {
-                if i >= self.source.len() {
-                    return b'\0'
-                }
-                self.source.as_bytes()[i]
-            }
+        let i = index.unwrap_or(self.current as usize);
+        if i >= self.source.len() {
+            return b'\0'
         }
+        self.source.as_bytes()[i]
+    }
+
+    fn newline(&mut self) {
+        self.line += 1;
+        self.current_column = 1;
     }
 
     fn is_at_end(&self) -> bool {
@@ -60,7 +82,10 @@ impl Scanner {
             b')' => self.add_token_nonliteral(RIGHT_PAREN),
             b'{' => self.add_token_nonliteral(LEFT_BRACE),
             b'}' => self.add_token_nonliteral(RIGHT_BRACE),
+            b'[' => self.add_token_nonliteral(LEFT_BRACKET),
+            b']' => self.add_token_nonliteral(RIGHT_BRACKET),
             b',' => self.add_token_nonliteral(COMMA),
+            b':' => self.add_token_nonliteral(COLON),
             b'.' => self.add_token_nonliteral(DOT),
             b'-' => self.add_token_nonliteral(MINUS),
             b'+' => self.add_token_nonliteral(PLUS),
@@ -73,9 +98,13 @@ impl Scanner {
                 }
             },
             b'=' => {
-                match self.match_second(b'=') {
-                    true => self.add_token_nonliteral(EQUAL_EQUAL),
-                    false => self.add_token_nonliteral(EQUAL)
+                // Only one of the two can follow, so `==>` is `==` then `>`
+                if self.match_second(b'=') {
+                    self.add_token_nonliteral(EQUAL_EQUAL)
+                } else if self.match_second(b'>') {
+                    self.add_token_nonliteral(FAT_ARROW)
+                } else {
+                    self.add_token_nonliteral(EQUAL)
                 }
             }
             b'<' => {
@@ -97,6 +126,9 @@ impl Scanner {
                         while !self.is_at_end() && self.get_source_char(None) != b'\n' {
                             self.advance();
                         }
+                        if self.keep_comments {
+                            self.add_token_nonliteral(COMMENT);
+                        }
                     }
                     false => self.add_token_nonliteral(SLASH)
                 }
@@ -104,7 +136,7 @@ impl Scanner {
 
             // Skip whitespace
             b' ' | b'\r' | b'\t' => (),
-            b'\n' => self.line += 1,
+            b'\n' => self.newline(),
 
             // Literals
             b'"' => self.string(),
@@ -123,13 +155,19 @@ impl Scanner {
         if self.get_source_char(None) != expected { return false }
 
         self.current += 1;
+        self.current_column += 1;
         true
     }
 
     fn advance(&mut self) -> u8 {
         let i = self.current;
         self.current += 1;
-        self.get_source_char(Some(i as usize))
+        let c = self.get_source_char(Some(i as usize));
+        // Continuation bytes belong to the same character as the byte before them
+        if c & 0xC0 != 0x80 {
+            self.current_column += 1;
+        }
+        c
     }
 
     fn add_token_nonliteral(&mut self, token_type: TokenType) {
@@ -140,16 +178,16 @@ impl Scanner {
         let bytes = self.source.as_bytes();
         let text = String::from_utf8_lossy(&bytes[self.start as usize..self.current as usize]);
         let text = text.into_owned();
-        let token = Token::new(token_type, text, literal, self.line);
+        let token = Token::new(token_type, text, literal, self.start_line, self.column);
         self.tokens.push(token);
     }
 
     fn string(&mut self) {
         while !self.is_at_end() && self.get_source_char(None) != b'"'  {
-            if self.get_source_char(None) == b'\n' {
-                self.line += 1
-            }
             self.advance();
+            if self.get_source_char(Some(self.current as usize - 1)) == b'\n' {
+                self.newline();
+            }
         }
         if self.is_at_end() {
             lox::error(self.line, "Unterminated string.");
@@ -175,17 +213,39 @@ impl Scanner {
             self.advance();
         }
 
+        // A fractional part or an exponent makes the literal a float, otherwise it is an integer
+        let mut is_float = false;
         if self.get_source_char(None) == b'.'
             && Scanner::is_digit(self.get_source_char(Some((self.current + 1) as usize))) {
+            is_float = true;
             self.advance();
             while Scanner::is_digit(self.get_source_char(None)) {
                 self.advance();
             }
         }
+
+        if matches!(self.get_source_char(None), b'e' | b'E') {
+            let sign = matches!(self.get_source_char(Some((self.current + 1) as usize)), b'+' | b'-') as i32;
+            if Scanner::is_digit(self.get_source_char(Some((self.current + 1 + sign) as usize))) {
+                is_float = true;
+                self.current += 1 + sign;
+                self.current_column += 1 + sign;
+                while Scanner::is_digit(self.get_source_char(None)) {
+                    self.advance();
+                }
+            }
+        }
+
         let bytes = self.source.as_bytes();
         let value = str::from_utf8(&bytes[self.start as usize..self.current as usize]).unwrap();
-        let value = f64::from_str(value).unwrap();
-        self.add_token(NUMBER, LOX_NUMBER(value));
+        if is_float {
+            self.add_token(NUMBER, LOX_NUMBER(f64::from_str(value).unwrap()));
+            return;
+        }
+        match i64::from_str(value) {
+            Ok(value) => self.add_token(NUMBER, LOX_INT(value)),
+            Err(_) => self.add_token(NUMBER, LOX_BIGINT(Rc::new(BigInt::parse(value).unwrap()))),
+        }
     }
 
     fn is_alpha(c: u8) -> bool {
@@ -204,12 +264,19 @@ impl Scanner {
         let value = str::from_utf8(&bytes[self.start as usize..self.current as usize]).unwrap();
         let token_type = match value {
             "and" => AND,
+            "break" => BREAK,
+            "catch" => CATCH,
             "class" => CLASS,
+            "const" => CONST,
+            "continue" => CONTINUE,
             "else" => ELSE,
+            "enum" => ENUM,
             "false" => FALSE,
             "for" => FOR,
             "fun" => FUN,
             "if" => IF,
+            "in" => IN,
+            "match" => MATCH,
             "nil" => NIL,
             "or" => OR,
             "print" => PRINT,
@@ -217,8 +284,10 @@ impl Scanner {
             "super" => SUPER,
             "this" => THIS,
             "true" => TRUE,
+            "try" => TRY,
             "var" => VAR,
             "while" => WHILE,
+            "yield" => YIELD,
             _ => IDENTIFIER,
         };
         self.add_token_nonliteral(token_type);
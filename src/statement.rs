@@ -1,6 +1,7 @@
 use std::rc::Rc;
 use crate::expression::Expr;
 use crate::function_object::FunctionObject;
+use crate::pattern::MatchArm;
 use crate::token::Token;
 
 pub enum Stmt {
@@ -8,16 +9,36 @@ pub enum Stmt {
         statements: Vec<Stmt>,
     },
 
+    Break {
+        keyword: Token,
+    },
+
     Class {
         name: Token,
         superclass: Option<Box<Expr>>,
         methods: Vec<Stmt>,
     },
 
+    Continue {
+        keyword: Token,
+    },
+
+    Enum {
+        name: Token,
+        variants: Vec<EnumVariantDecl>,
+    },
+
     Expression {
         expression: Box<Expr>,
     },
 
+    ForIn {
+        keyword: Token,
+        name: Token,
+        iterable: Box<Expr>,
+        body: Box<Stmt>,
+    },
+
     Function {
         ptr: Rc<FunctionObject>
     },
@@ -28,6 +49,12 @@ pub enum Stmt {
         else_branch: Box<Stmt>,
     },
 
+    Match {
+        keyword: Token,
+        subject: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
+
     Print {
         expression: Box<Expr>,
     },
@@ -37,14 +64,53 @@ pub enum Stmt {
         value: Box<Expr>,
     },
 
-    Var {
+    // try { ... } catch (name) { ... } -- `name` holds the message of the caught runtime error
+    Try {
+        body: Box<Stmt>,
         name: Token,
+        handler: Box<Stmt>,
+    },
+
+    Var {
+        target: VarTarget,
         initializer: Box<Expr>,
+        is_const: bool,
+    },
+
+    Yield {
+        keyword: Token,
+        value: Box<Expr>,
     },
 
     While {
         expression: Box<Expr>,
         body: Box<Stmt>,
+        // Kept apart from the body so that `continue` still runs a for-loop's increment
+        increment: Option<Box<Expr>>,
     },
 }
 
+pub struct EnumVariantDecl {
+    pub name: Token,
+    // Empty for unit variants such as `Red`
+    pub fields: Vec<Token>,
+}
+
+pub enum VarTarget {
+    // var name = value;
+    Name(Token),
+    // var [a, b] = list;
+    List { bracket: Token, names: Vec<Token> },
+    // var {x, y} = instance;
+    Fields { brace: Token, names: Vec<Token> },
+}
+
+impl VarTarget {
+    pub fn names(&self) -> Vec<&Token> {
+        match self {
+            VarTarget::Name(name) => vec![name],
+            VarTarget::List { names, .. } | VarTarget::Fields { names, .. } => names.iter().collect(),
+        }
+    }
+}
+
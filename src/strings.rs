@@ -0,0 +1,235 @@
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::rc::Rc;
+
+use crate::callable::Arity;
+use crate::class::LoxClass;
+use crate::class_instance::LoxInstance;
+use crate::interpreter::InterpreterError;
+use crate::native_class::{NativeConstructor, NativeMethod};
+use crate::token_literal::TokenLiteral::{self, LOX_BOOL, LOX_INT, LOX_LIST, LOX_MAP, LOX_STRING};
+
+// Largest string `repeat` will build, so a huge count fails with an error instead of aborting on allocation
+const MAX_REPEAT_BYTES: usize = 1 << 30;
+
+// String natives, all indexed by character rather than by byte
+#[derive(Clone, Copy)]
+pub enum StringNative {
+    Len,
+    Substring,
+    IndexOf,
+    Split,
+    Join,
+    Replace,
+    Trim,
+    Upper,
+    Lower,
+    StartsWith,
+    EndsWith,
+    Repeat,
+    CharAt,
+    Ord,
+    Chr,
+}
+
+impl StringNative {
+    pub const ALL: [StringNative; 15] = [
+        StringNative::Len,
+        StringNative::Substring,
+        StringNative::IndexOf,
+        StringNative::Split,
+        StringNative::Join,
+        StringNative::Replace,
+        StringNative::Trim,
+        StringNative::Upper,
+        StringNative::Lower,
+        StringNative::StartsWith,
+        StringNative::EndsWith,
+        StringNative::Repeat,
+        StringNative::CharAt,
+        StringNative::Ord,
+        StringNative::Chr,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StringNative::Len => "len",
+            StringNative::Substring => "substring",
+            StringNative::IndexOf => "indexOf",
+            StringNative::Split => "split",
+            StringNative::Join => "join",
+            StringNative::Replace => "replace",
+            StringNative::Trim => "trim",
+            StringNative::Upper => "upper",
+            StringNative::Lower => "lower",
+            StringNative::StartsWith => "startsWith",
+            StringNative::EndsWith => "endsWith",
+            StringNative::Repeat => "repeat",
+            StringNative::CharAt => "charAt",
+            StringNative::Ord => "ord",
+            StringNative::Chr => "chr",
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            StringNative::Len | StringNative::Trim | StringNative::Upper | StringNative::Lower
+            | StringNative::Ord | StringNative::Chr => 1,
+            StringNative::IndexOf | StringNative::Split | StringNative::Join | StringNative::StartsWith
+            | StringNative::EndsWith | StringNative::Repeat | StringNative::CharAt => 2,
+            StringNative::Substring | StringNative::Replace => 3,
+        }
+    }
+
+    pub fn call(&self, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match self {
+            StringNative::Len => match &arguments[0] {
+                // Collections have a length too
+                LOX_LIST(list) => Ok(LOX_INT(list.borrow().len() as i64)),
+                LOX_MAP(map) => Ok(LOX_INT(map.borrow().len() as i64)),
+                _ => Ok(LOX_INT(self.string(&arguments, 0)?.chars().count() as i64)),
+            },
+            StringNative::Substring => {
+                let string = self.string(&arguments, 0)?;
+                let length = string.chars().count();
+                let start = self.index(&arguments, 1, length)?;
+                let end = self.index(&arguments, 2, length)?;
+                if start > end {
+                    return Err(self.error(format!("start {start} is after end {end}.")));
+                }
+                Ok(StringNative::new_string(string.chars().skip(start).take(end - start).collect()))
+            }
+            StringNative::IndexOf => {
+                let string = self.string(&arguments, 0)?;
+                let needle = self.string(&arguments, 1)?;
+                let index = match string.find(needle.as_str()) {
+                    Some(byte_index) => string[..byte_index].chars().count() as i64,
+                    None => -1,
+                };
+                Ok(LOX_INT(index))
+            }
+            StringNative::Split => {
+                let string = self.string(&arguments, 0)?;
+                let separator = self.string(&arguments, 1)?;
+                // An empty separator splits the string into its characters
+                let parts: Vec<TokenLiteral> = match separator.is_empty() {
+                    true => string.chars().map(|c| StringNative::new_string(c.to_string())).collect(),
+                    false => string.split(separator.as_str()).map(|part| StringNative::new_string(part.to_string())).collect(),
+                };
+                Ok(LOX_LIST(Rc::new(parts.into())))
+            }
+            StringNative::Join => {
+                let LOX_LIST(list) = &arguments[0] else {
+                    return Err(self.error(format!("expects a list as argument 1 but got {}.", arguments[0])));
+                };
+                let separator = self.string(&arguments, 1)?;
+                let parts: Vec<String> = list.borrow().iter().map(|item| item.to_string()).collect();
+                Ok(StringNative::new_string(parts.join(separator.as_str())))
+            }
+            StringNative::Replace => {
+                let string = self.string(&arguments, 0)?;
+                let from = self.string(&arguments, 1)?;
+                let to = self.string(&arguments, 2)?;
+                if from.is_empty() {
+                    return Err(self.error(String::from("can't replace an empty string.")));
+                }
+                Ok(StringNative::new_string(string.replace(from.as_str(), to)))
+            }
+            StringNative::Trim => Ok(StringNative::new_string(self.string(&arguments, 0)?.trim().to_string())),
+            StringNative::Upper => Ok(StringNative::new_string(self.string(&arguments, 0)?.to_uppercase())),
+            StringNative::Lower => Ok(StringNative::new_string(self.string(&arguments, 0)?.to_lowercase())),
+            StringNative::StartsWith => {
+                let string = self.string(&arguments, 0)?;
+                Ok(LOX_BOOL(string.starts_with(self.string(&arguments, 1)?.as_str())))
+            }
+            StringNative::EndsWith => {
+                let string = self.string(&arguments, 0)?;
+                Ok(LOX_BOOL(string.ends_with(self.string(&arguments, 1)?.as_str())))
+            }
+            StringNative::Repeat => {
+                let string = self.string(&arguments, 0)?;
+                match &arguments[1] {
+                    LOX_INT(count) if *count >= 0 => {
+                        match usize::try_from(*count).ok().and_then(|count| string.len().checked_mul(count)) {
+                            Some(bytes) if bytes <= MAX_REPEAT_BYTES => Ok(StringNative::new_string(string.repeat(*count as usize))),
+                            _ => Err(self.error(format!("result would exceed the maximum string size of {MAX_REPEAT_BYTES} bytes."))),
+                        }
+                    }
+                    other => Err(self.error(format!("expects a non-negative integer as argument 2 but got {other}."))),
+                }
+            }
+            StringNative::CharAt => {
+                let string = self.string(&arguments, 0)?;
+                let length = string.chars().count();
+                let index = self.index(&arguments, 1, length)?;
+                match string.chars().nth(index) {
+                    Some(c) => Ok(StringNative::new_string(c.to_string())),
+                    None => Err(self.error(format!("index {index} out of range for length {length}."))),
+                }
+            }
+            StringNative::Ord => {
+                let string = self.string(&arguments, 0)?;
+                let mut chars = string.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(LOX_INT(c as i64)),
+                    _ => Err(self.error(format!("expects a single character but got \"{string}\"."))),
+                }
+            }
+            StringNative::Chr => match &arguments[0] {
+                LOX_INT(code) => match u32::try_from(*code).ok().and_then(char::from_u32) {
+                    Some(c) => Ok(StringNative::new_string(c.to_string())),
+                    None => Err(self.error(format!("{code} is not a valid character code."))),
+                },
+                other => Err(self.error(format!("expects an integer but got {other}."))),
+            },
+        }
+    }
+
+    fn new_string(string: String) -> TokenLiteral {
+        LOX_STRING(Rc::new(string))
+    }
+
+    fn error(&self, message: String) -> InterpreterError {
+        InterpreterError::NativeError(format!("{}() {message}", self.name()))
+    }
+
+    fn string<'a>(&self, arguments: &'a [TokenLiteral], position: usize) -> Result<&'a Rc<String>, InterpreterError> {
+        match &arguments[position] {
+            LOX_STRING(string) => Ok(string),
+            other => Err(self.error(format!("expects a string as argument {} but got {other}.", position + 1))),
+        }
+    }
+
+    /// A character position within a string of `length` characters; the length itself is allowed as an end bound
+    fn index(&self, arguments: &[TokenLiteral], position: usize, length: usize) -> Result<usize, InterpreterError> {
+        match &arguments[position] {
+            LOX_INT(index) if *index >= 0 && *index as u64 <= length as u64 => Ok(*index as usize),
+            LOX_INT(index) => Err(self.error(format!("index {index} out of range for length {length}."))),
+            other => Err(self.error(format!("expects an integer as argument {} but got {other}.", position + 1))),
+        }
+    }
+}
+
+/// StringBuilder -- a growable string, avoiding the copy made by every `+` when building strings in a loop
+pub fn string_builder_class() -> LoxClass {
+    fn buffer(instance: &Rc<LoxInstance>) -> Result<Rc<RefCell<String>>, InterpreterError> {
+        instance.handle::<RefCell<String>>()
+            .ok_or_else(|| InterpreterError::NativeError(String::from("StringBuilder method called on an instance without a buffer.")))
+    }
+
+    let constructor = NativeConstructor::new(Arity::Exact(0), |_, _| Ok(Rc::new(RefCell::new(String::new()))));
+    let methods = vec![
+        // Returns the builder itself so that appends can be chained
+        NativeMethod::new("append", Arity::Exact(1), |_, instance, arguments| {
+            write!(buffer(instance)?.borrow_mut(), "{}", arguments[0]).unwrap();
+            Ok(TokenLiteral::LOX_INSTANCE(Rc::clone(instance)))
+        }),
+        NativeMethod::new("length", Arity::Exact(0), |_, instance, _| {
+            Ok(TokenLiteral::LOX_INT(buffer(instance)?.borrow().chars().count() as i64))
+        }),
+        NativeMethod::new("build", Arity::Exact(0), |_, instance, _| {
+            Ok(TokenLiteral::LOX_STRING(Rc::new(buffer(instance)?.borrow().clone())))
+        }),
+    ];
+    LoxClass::new_native(String::from("StringBuilder"), constructor, methods)
+}
@@ -0,0 +1,126 @@
+use std::fs;
+
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::lox;
+use crate::statement::Stmt;
+use crate::token::Token;
+use crate::token_literal::TokenLiteral::{self, LOX_CALLABLE, LOX_NULL, LOX_STRING};
+
+const TEST_PREFIX: &str = "test_";
+
+// Natives for checking expectations, failing with a runtime error that carries the line of the call
+#[derive(Clone, Copy)]
+pub enum AssertNative {
+    Assert,
+    AssertEqual,
+}
+
+impl AssertNative {
+    pub const ALL: [AssertNative; 2] = [AssertNative::Assert, AssertNative::AssertEqual];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AssertNative::Assert => "assert",
+            AssertNative::AssertEqual => "assertEqual",
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        2
+    }
+
+    pub fn call(&self, arguments: Vec<TokenLiteral>) -> Result<TokenLiteral, InterpreterError> {
+        match self {
+            AssertNative::Assert if Interpreter::is_truthy(&arguments[0]) => Ok(LOX_NULL),
+            AssertNative::Assert => Err(self.error(format!("failed: {}", arguments[1]))),
+            AssertNative::AssertEqual if Interpreter::is_equal(arguments[0].clone(), arguments[1].clone()) => Ok(LOX_NULL),
+            AssertNative::AssertEqual => {
+                let (actual, expected) = (AssertNative::describe(&arguments[0]), AssertNative::describe(&arguments[1]));
+                Err(self.error(format!("failed: {actual} is not equal to {expected}")))
+            }
+        }
+    }
+
+    fn error(&self, message: String) -> InterpreterError {
+        InterpreterError::NativeError(format!("{}() {message}", self.name()))
+    }
+
+    // Quotes strings, so that `1` and `"1"` can be told apart in a failure
+    fn describe(value: &TokenLiteral) -> String {
+        match value {
+            LOX_STRING(string) => format!("\"{string}\""),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// Runs every `test_*` function found at the top level of the given files, see `lox::lox_files`
+/// Each test gets a fresh interpreter that has run the rest of its file first, so tests can't leak state into each other
+/// Returns the process exit code: 0 when everything passed
+pub fn run_tests(paths: &[String], allow_fs: bool) -> i32 {
+    let (mut passed, mut failed) = (0, 0);
+    for file in lox::lox_files(paths) {
+        println!("{}", file.display());
+        let source = match fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(err) => {
+                println!("  FAIL could not read the file: {err}");
+                failed += 1;
+                continue;
+            }
+        };
+
+        // Resolving once is enough, the resolution only depends on the source
+        lox::clear_error();
+        let mut resolved = Interpreter::new();
+        let Some(statements) = lox::compile(&mut resolved, source) else {
+            println!("  FAIL the file has syntax errors");
+            failed += 1;
+            continue;
+        };
+
+        for name in test_names(&statements) {
+            let mut interpreter = Interpreter::new();
+            interpreter.allow_fs = allow_fs;
+            interpreter.locals = resolved.locals.clone();
+            match run_test(&mut interpreter, &statements, name) {
+                Ok(()) => {
+                    println!("  ok   {}", name.lexeme);
+                    passed += 1;
+                }
+                Err(reason) => {
+                    println!("  FAIL {} {reason}", name.lexeme);
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    println!("{passed} passed, {failed} failed");
+    if failed > 0 { 1 } else { 0 }
+}
+
+// Top-level functions taking no parameters, in the order they are declared
+fn test_names(statements: &[Stmt]) -> Vec<&Token> {
+    statements.iter().filter_map(|statement| match statement {
+        Stmt::Function { ptr } if ptr.name.lexeme.starts_with(TEST_PREFIX) && ptr.params.is_empty() => Some(&ptr.name),
+        _ => None,
+    }).collect()
+}
+
+fn run_test(interpreter: &mut Interpreter, statements: &[Stmt], name: &Token) -> Result<(), String> {
+    let describe = |error: InterpreterError| match error {
+        InterpreterError::OperatorError { line, err_msg } => format!("[line {line}] {err_msg}"),
+        InterpreterError::Exit(code) => format!("called exit({code})"),
+        _ => unreachable!("Early return should not escape a function or the top level"),
+    };
+
+    for statement in statements {
+        interpreter.accept_statement(statement).map_err(|error| format!("while loading the file: {}", describe(error)))?;
+    }
+    let LOX_CALLABLE(test) = interpreter.global_env.get(name).map_err(describe)? else {
+        return Err(String::from("is no longer a function"));
+    };
+    interpreter.call_callable(test, Vec::new(), name.line).map_err(describe)?;
+    Ok(())
+}
@@ -7,15 +7,18 @@ pub struct Token {
     pub lexeme: String,
     pub literal: TokenLiteral,
     pub line: i32,
+    // 1-based, counted in characters from the start of the line; 0 for tokens made up by the interpreter
+    pub column: i32,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, literal: TokenLiteral, line: i32) -> Self {
+    pub fn new(token_type: TokenType, lexeme: String, literal: TokenLiteral, line: i32, column: i32) -> Self {
         Token {
             token_type,
             lexeme,
             literal,
-            line
+            line,
+            column
         }
     }
 }
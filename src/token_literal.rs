@@ -1,29 +1,99 @@
+use std::cell::RefCell;
 use std::fmt::{Debug, Display, Formatter};
 use std::rc::Rc;
 
+use crate::bigint::BigInt;
 use crate::callable::LoxCallable;
 use crate::class_instance::LoxInstance;
+use crate::enum_type::{EnumValue, LoxEnum};
+use crate::generator::Generator;
+use crate::map::LoxMap;
+
+thread_local! {
+    // Lists and maps currently being printed, so a collection that contains itself prints as `[...]`
+    static PRINTING: RefCell<Vec<*const ()>> = const { RefCell::new(Vec::new()) };
+}
 
 #[allow(non_camel_case_types)]
 #[derive(Clone)]
 pub enum TokenLiteral {
     LOX_NUMBER(f64),
+    LOX_INT(i64),
+    LOX_BIGINT(Rc<BigInt>),
     LOX_STRING(Rc<String>),
     LOX_BOOL(bool),
     LOX_CALLABLE(Rc<LoxCallable>),
     LOX_INSTANCE(Rc<LoxInstance>),
+    LOX_LIST(Rc<RefCell<Vec<TokenLiteral>>>),
+    LOX_MAP(Rc<RefCell<LoxMap>>),
+    LOX_GENERATOR(Rc<Generator>),
+    LOX_ENUM(Rc<LoxEnum>),
+    LOX_ENUM_VALUE(Rc<EnumValue>),
     LOX_NULL
 }
 
+impl TokenLiteral {
+    // Strings nested inside a collection are quoted so that `["1", 1]` stays readable
+    pub fn fmt_nested(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenLiteral::LOX_STRING(value) => write!(f, "\"{value}\""),
+            _ => write!(f, "{self}")
+        }
+    }
+
+    // Mirrors `JsonWriter::enter`: a collection already on the printing stack is elided instead of recursed into
+    fn fmt_collection<F>(pointer: *const (), elided: &str, f: &mut Formatter<'_>, body: F) -> std::fmt::Result
+    where
+        F: FnOnce(&mut Formatter<'_>) -> std::fmt::Result,
+    {
+        if PRINTING.with(|printing| printing.borrow().contains(&pointer)) {
+            return write!(f, "{elided}");
+        }
+        PRINTING.with(|printing| printing.borrow_mut().push(pointer));
+        let result = body(f);
+        PRINTING.with(|printing| printing.borrow_mut().pop());
+        result
+    }
+
+    // Whole floats keep a trailing `.0` so they can't be mistaken for integers,
+    // and very large or small magnitudes switch to exponent notation
+    fn fmt_float(number: f64, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let magnitude = number.abs();
+        if number.is_finite() && number != 0.0 && !(1e-7..1e21).contains(&magnitude) {
+            write!(f, "{number:e}")
+        } else if number.is_finite() && number.fract() == 0.0 {
+            write!(f, "{number:.1}")
+        } else {
+            write!(f, "{number}")
+        }
+    }
+}
+
 impl Display for TokenLiteral {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             TokenLiteral::LOX_STRING(value) => write!(f, "{value}"),
-            TokenLiteral::LOX_NUMBER(number) => write!(f, "{number}"),
+            TokenLiteral::LOX_NUMBER(number) => TokenLiteral::fmt_float(*number, f),
+            TokenLiteral::LOX_INT(number) => write!(f, "{number}"),
+            TokenLiteral::LOX_BIGINT(number) => write!(f, "{number}"),
             TokenLiteral::LOX_BOOL(boolean) => write!(f, "{boolean}"),
             TokenLiteral::LOX_NULL => write!(f, "nil"),
             TokenLiteral::LOX_CALLABLE(callable) => write!(f, "{callable}"),
-            TokenLiteral::LOX_INSTANCE(instance) => write!(f, "{instance}")
+            TokenLiteral::LOX_INSTANCE(instance) => write!(f, "{instance}"),
+            TokenLiteral::LOX_LIST(list) => TokenLiteral::fmt_collection(Rc::as_ptr(list) as *const (), "[...]", f, |f| {
+                write!(f, "[")?;
+                for (i, element) in list.borrow().iter().enumerate() {
+                    if i > 0 { write!(f, ", ")?; }
+                    element.fmt_nested(f)?;
+                }
+                write!(f, "]")
+            }),
+            TokenLiteral::LOX_MAP(map) => TokenLiteral::fmt_collection(Rc::as_ptr(map) as *const (), "{...}", f, |f| {
+                write!(f, "{}", map.borrow())
+            }),
+            TokenLiteral::LOX_GENERATOR(generator) => write!(f, "{generator}"),
+            TokenLiteral::LOX_ENUM(enum_type) => write!(f, "{enum_type}"),
+            TokenLiteral::LOX_ENUM_VALUE(value) => write!(f, "{value}")
         }
     }
 }
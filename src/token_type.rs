@@ -1,13 +1,13 @@
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-#[allow(non_camel_case_types)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 pub enum TokenType {
     // Single-character tokens.
-    LEFT_PAREN, RIGHT_PAREN, LEFT_BRACE, RIGHT_BRACE,
-    COMMA, DOT, MINUS, PLUS, SEMICOLON, SLASH, STAR,
+    LEFT_PAREN, RIGHT_PAREN, LEFT_BRACE, RIGHT_BRACE, LEFT_BRACKET, RIGHT_BRACKET,
+    COLON, COMMA, DOT, MINUS, PLUS, SEMICOLON, SLASH, STAR,
 
     // One or two character tokens.
     BANG, BANG_EQUAL,
-    EQUAL, EQUAL_EQUAL,
+    EQUAL, EQUAL_EQUAL, FAT_ARROW,
     GREATER, GREATER_EQUAL,
     LESS, LESS_EQUAL,
 
@@ -15,8 +15,11 @@ pub enum TokenType {
     IDENTIFIER, STRING, NUMBER,
 
     // Keywords.
-    AND, CLASS, ELSE, FALSE, FUN, FOR, IF, NIL, OR,
-    PRINT, RETURN, SUPER, THIS, TRUE, VAR, WHILE,
+    AND, BREAK, CATCH, CLASS, CONST, CONTINUE, ELSE, ENUM, FALSE, FUN, FOR, IF, IN, MATCH, NIL, OR,
+    PRINT, RETURN, SUPER, THIS, TRUE, TRY, VAR, WHILE, YIELD,
+
+    // Trivia, only produced for tools that ask for it, see `Scanner::with_comments`.
+    COMMENT,
 
     EOF
 }
\ No newline at end of file
@@ -0,0 +1,109 @@
+//! The interpreter driven as a library, the way a host program would embed it
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use rlox::lox;
+use rlox::{Arity, Interpreter, InterpreterError, LoxClass, LoxInstance, NativeConstructor, NativeMethod, TokenLiteral};
+
+// Runs the source with a `record(value)` native that collects whatever the script passes it
+fn run_recording(interpreter: &mut Interpreter, source: &str) -> Vec<String> {
+    let recorded = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&recorded);
+    interpreter.global_env.define_native("record", Arity::Exact(1), move |_, arguments| {
+        sink.borrow_mut().push(arguments[0].to_string());
+        Ok(TokenLiteral::LOX_NULL)
+    });
+    lox::run(interpreter, String::from(source));
+    let recorded = recorded.borrow().clone();
+    recorded
+}
+
+#[test]
+fn host_functions() {
+    let mut interpreter = Interpreter::new();
+    interpreter.global_env.define_native("scale", Arity::Exact(2), |_, arguments| match (&arguments[0], &arguments[1]) {
+        (TokenLiteral::LOX_INT(value), TokenLiteral::LOX_INT(factor)) => Ok(TokenLiteral::LOX_INT(value * factor)),
+        _ => Err(InterpreterError::NativeError(String::from("scale() expects two integers."))),
+    });
+    interpreter.global_env.define_native("total", Arity::AtLeast(0), |_, arguments| {
+        let total = arguments.iter().map(|argument| match argument {
+            TokenLiteral::LOX_INT(value) => *value,
+            _ => 0,
+        }).sum();
+        Ok(TokenLiteral::LOX_INT(total))
+    });
+
+    let recorded = run_recording(&mut interpreter, r#"
+        record(scale(21, 2));
+        record(total());
+        record(total(1, 2, 3));
+        record(scale);
+        try {
+            scale("a", 2);
+        } catch (error) {
+            record(error);
+        }
+    "#);
+
+    assert_eq!(recorded, [
+        "42",
+        "0",
+        "6",
+        "<native fn scale>",
+        "scale() expects two integers.",
+    ]);
+}
+
+// A counter whose count lives in host data wrapped by each instance
+fn counter_class() -> LoxClass {
+    fn count(instance: &Rc<LoxInstance>) -> Result<Rc<Cell<i64>>, InterpreterError> {
+        instance.handle::<Cell<i64>>()
+            .ok_or_else(|| InterpreterError::NativeError(String::from("Counter method called on an instance without a count.")))
+    }
+
+    let constructor = NativeConstructor::new(Arity::Exact(1), |_, arguments| match &arguments[0] {
+        TokenLiteral::LOX_INT(start) => Ok(Rc::new(Cell::new(*start))),
+        _ => Err(InterpreterError::NativeError(String::from("Counter() expects an integer start."))),
+    });
+    let methods = vec![
+        NativeMethod::new("increment", Arity::Exact(0), |_, instance, _| {
+            let count = count(instance)?;
+            count.set(count.get() + 1);
+            Ok(TokenLiteral::LOX_INSTANCE(Rc::clone(instance)))
+        }),
+        NativeMethod::new("value", Arity::Exact(0), |_, instance, _| Ok(TokenLiteral::LOX_INT(count(instance)?.get()))),
+    ];
+    LoxClass::new_native(String::from("Counter"), constructor, methods)
+}
+
+#[test]
+fn host_classes() {
+    let mut interpreter = Interpreter::new();
+    interpreter.global_env.define_native_class(counter_class());
+
+    let recorded = run_recording(&mut interpreter, r#"
+        var first = Counter(10);
+        var second = Counter(0);
+        first.increment().increment();
+        second.increment();
+        record(first.value());
+        record(second.value());
+        record(Counter);
+        second.label = "fields still work";
+        record(second.label);
+        try {
+            Counter("ten");
+        } catch (error) {
+            record(error);
+        }
+    "#);
+
+    assert_eq!(recorded, [
+        "12",
+        "1",
+        "Counter",
+        "fields still work",
+        "Counter() expects an integer start.",
+    ]);
+}
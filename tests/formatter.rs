@@ -0,0 +1,67 @@
+//! Fixtures for `rlox fmt` under `tests/fmt`
+//! Each `name.input.lox` is formatted in a temporary copy and must come out as `name.expected.lox`,
+//! which `rlox fmt --check` must accept as it is and which must run exactly like the input.
+//! An input without an expected file has errors, so formatting must fail with 65 and leave it untouched.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const FIXTURE_DIR: &str = "tests/fmt";
+const INPUT_SUFFIX: &str = ".input.lox";
+const EXPECTED_SUFFIX: &str = ".expected.lox";
+
+fn rlox(args: &[&Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rlox")).args(args).output().unwrap()
+}
+
+fn check_fixture(input: &Path, scratch: &Path) -> Vec<String> {
+    let name = input.file_name().unwrap().to_str().unwrap().strip_suffix(INPUT_SUFFIX).unwrap();
+    let expected_path = input.with_file_name(format!("{name}{EXPECTED_SUFFIX}"));
+    let source = fs::read_to_string(input).unwrap();
+
+    let copy = scratch.join(format!("{name}.lox"));
+    fs::write(&copy, &source).unwrap();
+    let fmt = rlox(&[Path::new("fmt"), &copy]);
+    let formatted = fs::read_to_string(&copy).unwrap();
+
+    let mut failures = Vec::new();
+    let Ok(expected) = fs::read_to_string(&expected_path) else {
+        if fmt.status.code() != Some(65) {
+            failures.push(format!("{name}: expected fmt to exit with 65 but got {:?}", fmt.status.code()));
+        }
+        if formatted != source {
+            failures.push(format!("{name}: fmt rewrote a file with errors"));
+        }
+        return failures;
+    };
+
+    if formatted != expected {
+        failures.push(format!("{name}: formatted output differs from {}:\n{formatted}", expected_path.display()));
+    }
+    let check = rlox(&[Path::new("fmt"), Path::new("--check"), &expected_path]);
+    if !check.status.success() {
+        failures.push(format!("{name}: formatting the expected output again changes it"));
+    }
+    let (before, after) = (rlox(&[input]), rlox(&[&expected_path]));
+    if before.stdout != after.stdout || before.status.code() != after.status.code() {
+        failures.push(format!("{name}: the formatted script runs differently from the input"));
+    }
+    failures
+}
+
+#[test]
+fn fmt_fixtures() {
+    let mut inputs: Vec<PathBuf> = fs::read_dir(FIXTURE_DIR).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_str().is_some_and(|path| path.ends_with(INPUT_SUFFIX)))
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "no fixtures found under {FIXTURE_DIR}");
+
+    let scratch = std::env::temp_dir().join(format!("rlox_fmt_fixtures_{}", std::process::id()));
+    fs::create_dir_all(&scratch).unwrap();
+    let failures: Vec<String> = inputs.iter().flat_map(|input| check_fixture(input, &scratch)).collect();
+    fs::remove_dir_all(&scratch).unwrap();
+    assert!(failures.is_empty(), "{} fixture checks failed\n\n{}", failures.len(), failures.join("\n\n"));
+}
@@ -0,0 +1,110 @@
+//! Conformance suite in the style of the Crafting Interpreters tests
+//! Every `.lox` file under `tests/lox` is run through the `rlox` binary, and its stdout, stderr and exit code
+//! are compared against the annotations in its comments:
+//!
+//! - `// expect: text` is the next line printed to stdout
+//! - `// expect runtime error: message` is reported on stderr for the annotated line, and the run exits with 70
+//! - `// expect syntax error: at 'x' -- message` is reported on stderr for the annotated line, and the run exits with 65
+//! - `// [line N] Syntax Error: ...` or `// [line N] Runtime Error: ...` is reported on stderr as written, for errors on
+//!   lines that can't hold an annotation themselves, like the last line of a file without a trailing newline
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SUITE_DIR: &str = "tests/lox";
+
+const EXPECT: &str = "// expect: ";
+const EXPECT_RUNTIME_ERROR: &str = "// expect runtime error: ";
+const EXPECT_SYNTAX_ERROR: &str = "// expect syntax error: ";
+const EXPECT_ERROR_AT_LINE: &str = "// [line ";
+
+#[derive(Default)]
+struct Expectations {
+    stdout: Vec<String>,
+    stderr: Vec<String>,
+    exit_code: i32,
+}
+
+impl Expectations {
+    fn parse(source: &str) -> Self {
+        let mut expectations = Expectations::default();
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            if let Some((_, text)) = line.split_once(EXPECT) {
+                expectations.stdout.push(String::from(text));
+            } else if let Some((_, message)) = line.split_once(EXPECT_RUNTIME_ERROR) {
+                expectations.stderr.push(format!("[line {line_number}] Runtime Error: {message}"));
+                expectations.exit_code = 70;
+            } else if let Some((_, message)) = line.split_once(EXPECT_SYNTAX_ERROR) {
+                expectations.stderr.push(format!("[line {line_number}] Syntax Error: {message}"));
+                expectations.exit_code = 65;
+            } else if let Some((_, rest)) = line.split_once(EXPECT_ERROR_AT_LINE) {
+                let error = format!("[line {rest}");
+                expectations.exit_code = if error.contains("] Syntax Error: ") { 65 } else { 70 };
+                expectations.stderr.push(error);
+            }
+        }
+        expectations
+    }
+}
+
+fn collect_scripts(dir: &Path, scripts: &mut Vec<PathBuf>) {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            collect_scripts(&entry, scripts);
+        } else if entry.extension().is_some_and(|extension| extension == "lox") {
+            scripts.push(entry);
+        }
+    }
+}
+
+// The expected lines against the actual ones, from the first line where they differ
+fn diff(stream: &str, expected: &[String], actual: &[&str]) -> Option<String> {
+    if expected.iter().map(String::as_str).eq(actual.iter().copied()) {
+        return None;
+    }
+    let common = expected.iter().zip(actual).take_while(|(e, a)| e == *a).count();
+    let mut report = format!("  {stream} differs after {common} matching lines:\n");
+    for line in &expected[common..] {
+        report.push_str(&format!("  - {line}\n"));
+    }
+    for line in &actual[common..] {
+        report.push_str(&format!("  + {line}\n"));
+    }
+    Some(report)
+}
+
+fn check_script(script: &Path) -> Option<String> {
+    let source = fs::read_to_string(script).unwrap();
+    let expected = Expectations::parse(&source);
+    let output = Command::new(env!("CARGO_BIN_EXE_rlox")).arg(script).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    let mut failures = Vec::new();
+    failures.extend(diff("stdout", &expected.stdout, &stdout.lines().collect::<Vec<_>>()));
+    failures.extend(diff("stderr", &expected.stderr, &stderr.lines().collect::<Vec<_>>()));
+    let exit_code = output.status.code().unwrap_or(-1);
+    if exit_code != expected.exit_code {
+        failures.push(format!("  expected exit code {} but got {exit_code}\n", expected.exit_code));
+    }
+
+    if failures.is_empty() {
+        None
+    } else {
+        Some(format!("{}:\n{}", script.display(), failures.concat()))
+    }
+}
+
+#[test]
+fn golden_files() {
+    let mut scripts = Vec::new();
+    collect_scripts(Path::new(SUITE_DIR), &mut scripts);
+    assert!(!scripts.is_empty(), "no scripts found under {SUITE_DIR}");
+
+    let failures: Vec<String> = scripts.iter().filter_map(|script| check_script(script)).collect();
+    assert!(failures.is_empty(), "{} of {} scripts failed\n\n{}", failures.len(), scripts.len(), failures.join("\n"));
+}
@@ -0,0 +1,63 @@
+//! Fixtures for `rlox lint` under `tests/lint`
+//! Each file is linted and the warnings on stdout are compared against the annotations in its comments:
+//!
+//! - `// expect lint: column: warning[code]: message` is reported for the annotated line
+//! - `// expect lint exit: code` is the exit code, which otherwise is 1 with warnings and 0 without
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FIXTURE_DIR: &str = "tests/lint";
+
+const EXPECT_LINT: &str = "// expect lint: ";
+const EXPECT_EXIT: &str = "// expect lint exit: ";
+
+fn check_fixture(path: &Path) -> Option<String> {
+    let source = fs::read_to_string(path).unwrap();
+    let mut expected = Vec::new();
+    let mut exit_code = None;
+    for (index, line) in source.lines().enumerate() {
+        if let Some((_, lint)) = line.split_once(EXPECT_LINT) {
+            expected.push(format!("{}:{}:{lint}", path.display(), index + 1));
+        } else if let Some((_, code)) = line.split_once(EXPECT_EXIT) {
+            exit_code = Some(code.trim().parse::<i32>().unwrap());
+        }
+    }
+    let exit_code = exit_code.unwrap_or(if expected.is_empty() { 0 } else { 1 });
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rlox")).arg("lint").arg(path).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let actual: Vec<&str> = stdout.lines().collect();
+
+    let mut failures = Vec::new();
+    for lint in expected.iter().filter(|lint| !actual.contains(&lint.as_str())) {
+        failures.push(format!("  missing: {lint}\n"));
+    }
+    for lint in actual.iter().filter(|lint| !expected.iter().any(|expected| expected == *lint)) {
+        failures.push(format!("  unexpected: {lint}\n"));
+    }
+    let actual_code = output.status.code().unwrap_or(-1);
+    if actual_code != exit_code {
+        failures.push(format!("  expected exit code {exit_code} but got {actual_code}\n"));
+    }
+
+    if failures.is_empty() {
+        None
+    } else {
+        Some(format!("{}:\n{}", path.display(), failures.concat()))
+    }
+}
+
+#[test]
+fn lint_fixtures() {
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(FIXTURE_DIR).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "lox"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no fixtures found under {FIXTURE_DIR}");
+
+    let failures: Vec<String> = fixtures.iter().filter_map(|fixture| check_fixture(fixture)).collect();
+    assert!(failures.is_empty(), "{} of {} fixtures failed\n\n{}", failures.len(), fixtures.len(), failures.join("\n"));
+}
@@ -0,0 +1,83 @@
+//! Drives `rlox lsp` over stdin and stdout like an editor would, from `initialize` through `exit`
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const URI: &str = "file:///project/add.lox";
+// The document as a JSON string; `scratch` is unused so that opening it publishes a lint
+const SOURCE: &str = r#""fun add(a, b) {\n  var scratch = 1;\n  return a + b;\n}\nprint add(1, 2);\n""#;
+
+fn frame(body: String) -> String {
+    format!("Content-Length: {}\r\n\r\n{body}", body.len())
+}
+
+// The bodies of the messages the server wrote, in order
+fn bodies(mut output: &str) -> Vec<String> {
+    let mut bodies = Vec::new();
+    while let Some((header, rest)) = output.split_once("\r\n\r\n") {
+        let length: usize = header.strip_prefix("Content-Length: ").unwrap().parse().unwrap();
+        bodies.push(String::from(&rest[..length]));
+        output = &rest[length..];
+    }
+    bodies
+}
+
+#[test]
+fn editor_session() {
+    // Both requests point at `add` in the call on the last line
+    let position = format!(r#"{{"textDocument":{{"uri":"{URI}"}},"position":{{"line":4,"character":7}}}}"#);
+    let input = [
+        String::from(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#),
+        String::from(r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#),
+        format!(r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"{URI}","languageId":"lox","version":1,"text":{SOURCE}}}}}}}"#),
+        format!(r#"{{"jsonrpc":"2.0","id":2,"method":"textDocument/definition","params":{position}}}"#),
+        format!(r#"{{"jsonrpc":"2.0","id":3,"method":"textDocument/hover","params":{position}}}"#),
+        String::from(r#"{"jsonrpc":"2.0","id":4,"method":"shutdown"}"#),
+        String::from(r#"{"jsonrpc":"2.0","method":"exit"}"#),
+    ].into_iter().map(frame).collect::<String>();
+
+    let mut server = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .args(["lsp", "--stdio"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    server.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = server.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0), "a shutdown before exit should end the server with 0");
+
+    let bodies = bodies(&String::from_utf8(output.stdout).unwrap());
+    assert_eq!(bodies.len(), 5, "unexpected messages from the server: {bodies:#?}");
+    assert!(bodies[0].starts_with(r#"{"jsonrpc":"2.0","id":1,"result":{"capabilities":{"#), "initialize: {}", bodies[0]);
+    assert!(bodies[0].contains(r#""definitionProvider":true"#) && bodies[0].contains(r#""hoverProvider":true"#), "initialize: {}", bodies[0]);
+
+    let diagnostics = &bodies[1];
+    assert!(diagnostics.contains(r#""method":"textDocument/publishDiagnostics""#), "didOpen: {diagnostics}");
+    assert!(diagnostics.contains(r#""code":"unused-variable""#), "didOpen: {diagnostics}");
+    assert!(diagnostics.contains(r#""range":{"start":{"line":1,"character":6},"end":{"line":1,"character":13}}"#), "didOpen: {diagnostics}");
+
+    assert_eq!(bodies[2], format!(r#"{{"jsonrpc":"2.0","id":2,"result":{{"uri":"{URI}","range":{{"start":{{"line":0,"character":4}},"end":{{"line":0,"character":7}}}}}}}}"#));
+    assert_eq!(bodies[3], concat!(
+        r#"{"jsonrpc":"2.0","id":3,"result":{"contents":{"kind":"markdown","value":"```lox\nfun add(a, b)\n```"},"#,
+        r#""range":{"start":{"line":4,"character":6},"end":{"line":4,"character":9}}}}"#,
+    ));
+    assert_eq!(bodies[4], r#"{"jsonrpc":"2.0","id":4,"result":null}"#);
+}
+
+#[test]
+fn client_closes_output() {
+    let mut server = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Nothing reads the responses, so the first one fails to write
+    drop(server.stdout.take());
+    let initialize = frame(String::from(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#));
+    server.stdin.take().unwrap().write_all(initialize.as_bytes()).unwrap();
+    let output = server.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(1), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stderr.is_empty(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+}